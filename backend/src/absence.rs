@@ -0,0 +1,230 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::models::{AbsenceRequest, AbsenceType, Holiday, User, WorkSchedule};
+
+/// Rules that apply to every request of one absence type.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AbsencePolicy {
+    pub absence_type: AbsenceType,
+    /// Requests stay pending until a manager decides; otherwise they are
+    /// approved on creation
+    pub requires_approval: bool,
+    /// Longest single request in calendar days
+    pub max_days: Option<i64>,
+    /// Documentation is needed for requests longer than this many days
+    pub documentation_after_days: Option<i64>,
+    /// Approved days are deducted from the employee's balance
+    pub uses_balance: bool,
+    /// Time off work; remote work is not
+    pub is_leave: bool,
+}
+
+pub fn policy(absence_type: AbsenceType) -> AbsencePolicy {
+    let (requires_approval, max_days, documentation_after_days, uses_balance) = match absence_type {
+        AbsenceType::Vacation => (true, Some(30), None, true),
+        AbsenceType::Sick => (false, None, Some(3), false),
+        AbsenceType::Parental => (true, Some(365), Some(0), false),
+        AbsenceType::Unpaid => (true, Some(90), None, false),
+        AbsenceType::Training => (true, Some(10), None, true),
+        AbsenceType::Remote => (false, Some(20), None, false),
+    };
+    AbsencePolicy {
+        absence_type,
+        requires_approval,
+        max_days,
+        documentation_after_days,
+        uses_balance,
+        is_leave: absence_type != AbsenceType::Remote,
+    }
+}
+
+/// The user's own time zone, or `default` when unset or unknown.
+pub fn time_zone_of(user: &User, default: Tz) -> Tz {
+    user.time_zone
+        .as_deref()
+        .and_then(|tz| tz.parse().ok())
+        .unwrap_or(default)
+}
+
+/// First and last local dates covered by an absence.
+pub fn local_dates(absence: &AbsenceRequest, time_zone: Tz) -> (NaiveDate, NaiveDate) {
+    (
+        local_date(absence.start_date, time_zone),
+        local_date(absence.end_date, time_zone),
+    )
+}
+
+/// Calendar date of `at` in the employee's time zone.
+pub fn local_date(at: DateTime<Utc>, time_zone: Tz) -> NaiveDate {
+    at.with_timezone(&time_zone).date_naive()
+}
+
+/// Whether `absence` and a request from `first` to `last` claim the same
+/// half day, comparing local dates in `time_zone` as days are charged. A
+/// request may start at midday on the day another ends at midday.
+pub fn overlaps(
+    absence: &AbsenceRequest,
+    time_zone: Tz,
+    first: NaiveDate,
+    last: NaiveDate,
+    start_half_day: bool,
+    end_half_day: bool,
+) -> bool {
+    let (absence_first, absence_last) = local_dates(absence, time_zone);
+    let (start, end) = half_day_range(absence_first, absence_last, absence.start_half_day, absence.end_half_day);
+    let (other_start, other_end) = half_day_range(first, last, start_half_day, end_half_day);
+    start <= other_end && other_start <= end
+}
+
+/// Numbers the half days from the morning of `first` to the afternoon of
+/// `last`, both included; midday starts and ends leave out a half.
+fn half_day_range(first: NaiveDate, last: NaiveDate, start_half_day: bool, end_half_day: bool) -> (i64, i64) {
+    let morning = |date: NaiveDate| i64::from(date.num_days_from_ce()) * 2;
+    (
+        morning(first) + i64::from(start_half_day),
+        morning(last) + 1 - i64::from(end_half_day),
+    )
+}
+
+/// Number of calendar days from `first` to `last`, counting both ends.
+pub fn calendar_days(first: NaiveDate, last: NaiveDate) -> i64 {
+    (last - first).num_days() + 1
+}
+
+/// Working days from `first` to `last`: each day counts as much as the
+/// schedule says, holidays count nothing, and half-day starts and ends
+/// count half.
+pub fn working_days(
+    first: NaiveDate,
+    last: NaiveDate,
+    start_half_day: bool,
+    end_half_day: bool,
+    schedule: &WorkSchedule,
+    holidays: &[Holiday],
+) -> f64 {
+    let days: f64 = first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .filter(|day| holidays.binary_search_by_key(day, |h| h.date).is_err())
+        .map(|day| {
+            let half = (start_half_day && day == first) || (end_half_day && day == last);
+            schedule.day(day.weekday()) * if half { 0.5 } else { 1.0 }
+        })
+        .sum();
+    crate::leave::round_days(days)
+}
+
+/// A one-day absence that starts and ends at midday would last no time
+/// at all; half a day off is a start or an end at midday.
+pub fn check_half_days(first: NaiveDate, last: NaiveDate, start_half_day: bool, end_half_day: bool) -> Result<(), String> {
+    if first == last && start_half_day && end_half_day {
+        return Err("a single-day absence cannot both start and end at midday".to_string());
+    }
+    Ok(())
+}
+
+/// Days a request is charged against the employee's balance.
+pub fn requested_days(absence: &AbsenceRequest) -> f64 {
+    absence.working_days
+}
+
+/// Checks a new request of `days` calendar days against its type's policy.
+pub fn check_policy(policy: &AbsencePolicy, days: i64, documentation: Option<&str>) -> Result<(), String> {
+    if let Some(max_days) = policy.max_days {
+        if days > max_days {
+            return Err(format!(
+                "{:?} absences are limited to {} days",
+                policy.absence_type, max_days
+            ));
+        }
+    }
+    if let Some(after_days) = policy.documentation_after_days {
+        if days > after_days && documentation.is_none_or(|d| d.trim().is_empty()) {
+            return Err(format!(
+                "{:?} absences longer than {} days need documentation",
+                policy.absence_type, after_days
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AbsenceStatus;
+    use crate::test_support::{absence, date};
+
+    fn holiday(day: u32) -> Holiday {
+        Holiday { date: date(day), name: "Holiday".to_string() }
+    }
+
+    #[test]
+    fn counts_scheduled_days_only() {
+        let schedule = WorkSchedule::default();
+        assert_eq!(working_days(date(1), date(7), false, false, &schedule, &[]), 5.0);
+        assert_eq!(working_days(date(6), date(7), false, false, &schedule, &[]), 0.0);
+        assert_eq!(working_days(date(1), date(14), false, false, &schedule, &[]), 10.0);
+    }
+
+    #[test]
+    fn skips_holidays() {
+        let schedule = WorkSchedule::default();
+        let holidays = [holiday(1), holiday(6)];
+        assert_eq!(working_days(date(1), date(7), false, false, &schedule, &holidays), 4.0);
+    }
+
+    #[test]
+    fn counts_half_day_starts_and_ends() {
+        let schedule = WorkSchedule::default();
+        assert_eq!(working_days(date(1), date(5), true, false, &schedule, &[]), 4.5);
+        assert_eq!(working_days(date(1), date(5), true, true, &schedule, &[]), 4.0);
+        assert_eq!(working_days(date(3), date(3), true, false, &schedule, &[]), 0.5);
+        assert_eq!(working_days(date(3), date(3), false, true, &schedule, &[]), 0.5);
+        // A single day cannot both start and end at midday
+        assert!(check_half_days(date(3), date(3), true, true).is_err());
+        assert!(check_half_days(date(3), date(4), true, true).is_ok());
+        // Half a day off on a day off is still nothing
+        assert_eq!(working_days(date(1), date(6), false, true, &schedule, &[]), 5.0);
+    }
+
+    #[test]
+    fn follows_part_time_schedules() {
+        let schedule = WorkSchedule { friday: 0.5, wednesday: 0.0, ..WorkSchedule::default() };
+        assert_eq!(working_days(date(1), date(7), false, false, &schedule, &[]), 3.5);
+        assert_eq!(working_days(date(5), date(5), true, false, &schedule, &[]), 0.25);
+    }
+
+    #[test]
+    fn dates_absences_in_the_local_time_zone() {
+        let at = "2024-01-01T23:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(local_date(at, chrono_tz::UTC), date(1));
+        assert_eq!(local_date(at, chrono_tz::Europe::Berlin), date(2));
+        assert_eq!(calendar_days(date(1), date(2)), 2);
+    }
+
+    #[test]
+    fn overlaps_by_local_half_days() {
+        let morning = absence("ada", 8, 8, AbsenceStatus::Approved);
+        let berlin = chrono_tz::Europe::Berlin;
+        // Any other request on the same day, whatever its times
+        assert!(overlaps(&morning, chrono_tz::UTC, date(8), date(8), false, false));
+        assert!(overlaps(&morning, chrono_tz::UTC, date(5), date(8), false, false));
+        // Ending at 17:00 UTC on the 8th does not reach the 9th
+        assert!(!overlaps(&morning, chrono_tz::UTC, date(9), date(10), false, false));
+        // 23:30 UTC on the 7th is already the 8th in Berlin
+        let late = AbsenceRequest {
+            end_date: "2024-01-07T23:30:00Z".parse().unwrap(),
+            ..absence("ada", 6, 7, AbsenceStatus::Approved)
+        };
+        assert!(!overlaps(&late, chrono_tz::UTC, date(8), date(8), false, false));
+        assert!(overlaps(&late, berlin, date(8), date(8), false, false));
+
+        // A midday end leaves the afternoon to a request starting at midday
+        let until_midday = AbsenceRequest { end_half_day: true, ..absence("ada", 8, 9, AbsenceStatus::Approved) };
+        assert!(!overlaps(&until_midday, chrono_tz::UTC, date(9), date(10), true, false));
+        assert!(overlaps(&until_midday, chrono_tz::UTC, date(9), date(10), false, false));
+    }
+}
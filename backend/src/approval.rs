@@ -0,0 +1,295 @@
+use chrono::NaiveDate;
+
+use crate::models::{
+    AbsenceRequest, AbsenceType, ApprovalRule, ApprovalStep, ApprovalStepStatus, Approver, Delegation, User,
+    UserStatus,
+};
+use crate::org::UsersById;
+
+/// Steps for a new request: those of the first matching rule, or a
+/// single line manager approval when no rule matches.
+pub fn chain_for(rules: &[ApprovalRule], absence_type: AbsenceType, working_days: f64) -> Vec<Approver> {
+    rules
+        .iter()
+        .find(|rule| {
+            rule.absence_type.is_none_or(|t| t == absence_type)
+                && rule.min_working_days.is_none_or(|min| working_days >= min)
+        })
+        .map(|rule| rule.steps.clone())
+        .unwrap_or_else(|| vec![Approver::LineManager])
+}
+
+/// Resolves each approver to a person where possible. Steps nobody could
+/// decide fall back to any manager: a line manager step when the requester
+/// has no active manager, and a named approver who is the requester or is
+/// not an active user.
+pub fn build_steps(approvers: Vec<Approver>, by_id: &UsersById, requester: &User) -> Vec<ApprovalStep> {
+    let can_decide = |id: &str| id != requester.id && by_id.get(id).is_some_and(|u| u.status == UserStatus::Active);
+    approvers
+        .into_iter()
+        .map(|approver| {
+            let (approver, assigned_to) = match approver {
+                Approver::LineManager => match requester.manager_id.as_deref() {
+                    Some(manager_id) if can_decide(manager_id) => {
+                        (Approver::LineManager, Some(manager_id.to_string()))
+                    }
+                    _ => (Approver::AnyManager, None),
+                },
+                Approver::User { user_id } if can_decide(&user_id) => {
+                    (Approver::User { user_id: user_id.clone() }, Some(user_id))
+                }
+                Approver::User { .. } | Approver::AnyManager => (Approver::AnyManager, None),
+            };
+            ApprovalStep {
+                approver,
+                assigned_to,
+                status: ApprovalStepStatus::Waiting,
+                decided_by: None,
+                on_behalf_of: None,
+                decided_at: None,
+                comment: None,
+            }
+        })
+        .collect()
+}
+
+/// Hands the waiting steps assigned to a removed user over to any manager.
+pub fn release_steps_of(absences: &mut [AbsenceRequest], user_id: &str) {
+    for step in absences.iter_mut().flat_map(|a| a.approvals.iter_mut()) {
+        if step.status == ApprovalStepStatus::Waiting && step.assigned_to.as_deref() == Some(user_id) {
+            step.approver = Approver::AnyManager;
+            step.assigned_to = None;
+        }
+    }
+}
+
+/// Index of the first step still waiting for a decision.
+pub fn current_step(absence: &AbsenceRequest) -> Option<usize> {
+    absence
+        .approvals
+        .iter()
+        .position(|s| s.status == ApprovalStepStatus::Waiting)
+}
+
+/// How someone may decide an approval step.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Authority {
+    /// As the assigned approver, or as a manager on a step any manager may decide
+    Own,
+    /// As the delegate of the assigned approver, whose ID is kept
+    Delegate(String),
+}
+
+impl Authority {
+    /// The approver someone decided for, recorded as `on_behalf_of`.
+    pub fn on_behalf_of(self) -> Option<String> {
+        match self {
+            Authority::Own => None,
+            Authority::Delegate(delegator_id) => Some(delegator_id),
+        }
+    }
+}
+
+/// Whether `user_id` may ever decide approval steps: managers, people
+/// with reports of their own and approvers named in a rule. Only they can
+/// hand their approvals over to a delegate.
+pub fn can_approve(user_id: &str, is_manager: bool, by_id: &UsersById, rules: &[ApprovalRule]) -> bool {
+    is_manager
+        || by_id.values().any(|u| u.manager_id.as_deref() == Some(user_id))
+        || rules
+            .iter()
+            .flat_map(|rule| rule.steps.iter())
+            .any(|step| matches!(step, Approver::User { user_id: named } if named == user_id))
+}
+
+/// Delegations to `delegate_id` in effect on `today`.
+pub fn delegations_held_by(delegations: &[Delegation], delegate_id: &str, today: NaiveDate) -> Vec<Delegation> {
+    delegations
+        .iter()
+        .filter(|d| d.delegate_id == delegate_id && d.start_date <= today && today <= d.end_date)
+        .cloned()
+        .collect()
+}
+
+/// How `actor` may decide `step` on `today`, or None if they may not.
+/// Nobody decides on their own request.
+pub fn authority(
+    step: &ApprovalStep,
+    absence: &AbsenceRequest,
+    actor_id: &str,
+    actor_is_manager: bool,
+    delegations: &[Delegation],
+    today: NaiveDate,
+) -> Option<Authority> {
+    if actor_id == absence.user_id {
+        return None;
+    }
+    match &step.assigned_to {
+        None => actor_is_manager.then_some(Authority::Own),
+        Some(assigned) if assigned == actor_id => Some(Authority::Own),
+        Some(assigned) => delegations
+            .iter()
+            .any(|d| {
+                &d.delegator_id == assigned
+                    && d.delegate_id == actor_id
+                    && d.start_date <= today
+                    && today <= d.end_date
+            })
+            .then(|| Authority::Delegate(assigned.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::models::AbsenceStatus;
+    use crate::test_support::{absence, date, user, user_store};
+
+    fn reporting_to(id: &str, manager_id: &str) -> User {
+        User { manager_id: Some(manager_id.to_string()), ..user(id) }
+    }
+
+    fn users() -> HashMap<String, User> {
+        user_store([
+            user("boss"),
+            reporting_to("ada", "boss"),
+            User { status: UserStatus::Invited, ..user("gone") },
+            reporting_to("bob", "gone"),
+            reporting_to("hr", "boss"),
+        ])
+    }
+
+    fn rule(absence_type: Option<AbsenceType>, min_working_days: Option<f64>, steps: Vec<Approver>) -> ApprovalRule {
+        ApprovalRule { absence_type, min_working_days, steps }
+    }
+
+    fn request_by(user_id: &str) -> AbsenceRequest {
+        absence(user_id, 1, 1, AbsenceStatus::Pending)
+    }
+
+    fn delegation(delegator_id: &str, delegate_id: &str, start: NaiveDate, end: NaiveDate) -> Delegation {
+        Delegation {
+            id: "delegation".to_string(),
+            delegator_id: delegator_id.to_string(),
+            delegate_id: delegate_id.to_string(),
+            start_date: start,
+            end_date: end,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn picks_the_first_matching_rule() {
+        let hr = Approver::User { user_id: "hr".to_string() };
+        let rules = [
+            rule(Some(AbsenceType::Parental), None, vec![hr.clone()]),
+            rule(None, Some(10.0), vec![Approver::LineManager, Approver::AnyManager]),
+        ];
+
+        assert_eq!(chain_for(&rules, AbsenceType::Parental, 1.0), vec![hr]);
+        assert_eq!(
+            chain_for(&rules, AbsenceType::Vacation, 10.0),
+            vec![Approver::LineManager, Approver::AnyManager]
+        );
+        assert_eq!(chain_for(&rules, AbsenceType::Vacation, 9.5), vec![Approver::LineManager]);
+    }
+
+    #[test]
+    fn resolves_line_managers_and_falls_back_to_any_manager() {
+        let users = users();
+        let by_id = crate::org::index_by_id(&users);
+        let approvers = vec![Approver::LineManager, Approver::User { user_id: "hr".to_string() }];
+
+        let steps = build_steps(approvers.clone(), &by_id, by_id["ada"]);
+        assert_eq!(steps[0].approver, Approver::LineManager);
+        assert_eq!(steps[0].assigned_to.as_deref(), Some("boss"));
+        assert_eq!(steps[1].assigned_to.as_deref(), Some("hr"));
+        assert!(steps.iter().all(|s| s.status == ApprovalStepStatus::Waiting));
+
+        // Inactive and missing managers leave the step to any manager
+        for requester in ["bob", "boss"] {
+            let steps = build_steps(approvers.clone(), &by_id, by_id[requester]);
+            assert_eq!(steps[0].approver, Approver::AnyManager);
+            assert_eq!(steps[0].assigned_to, None);
+        }
+    }
+
+    #[test]
+    fn leaves_named_approvers_who_cannot_decide_to_any_manager() {
+        let users = users();
+        let by_id = crate::org::index_by_id(&users);
+        let named = |user_id: &str| Approver::User { user_id: user_id.to_string() };
+
+        // Their own request, an inactive user and an unknown one
+        let steps = build_steps(vec![named("hr")], &by_id, by_id["hr"]);
+        assert_eq!((&steps[0].approver, &steps[0].assigned_to), (&Approver::AnyManager, &None));
+        for user_id in ["gone", "nobody"] {
+            let steps = build_steps(vec![named(user_id)], &by_id, by_id["ada"]);
+            assert_eq!((&steps[0].approver, &steps[0].assigned_to), (&Approver::AnyManager, &None));
+        }
+    }
+
+    #[test]
+    fn hands_waiting_steps_of_removed_users_to_any_manager() {
+        let users = users();
+        let by_id = crate::org::index_by_id(&users);
+        let mut request = request_by("ada");
+        request.approvals = build_steps(vec![Approver::LineManager, Approver::LineManager], &by_id, by_id["ada"]);
+        request.approvals[0].status = ApprovalStepStatus::Approved;
+
+        release_steps_of(std::slice::from_mut(&mut request), "boss");
+        assert_eq!(request.approvals[0].assigned_to.as_deref(), Some("boss"));
+        assert_eq!((&request.approvals[1].approver, &request.approvals[1].assigned_to), (&Approver::AnyManager, &None));
+    }
+
+    #[test]
+    fn grants_authority_to_approvers_and_current_delegates() {
+        let users = users();
+        let by_id = crate::org::index_by_id(&users);
+        let steps = build_steps(vec![Approver::LineManager, Approver::AnyManager], &by_id, by_id["ada"]);
+        let request = request_by("ada");
+        let delegations = [delegation("boss", "bob", date(1), date(5))];
+
+        assert_eq!(authority(&steps[0], &request, "boss", true, &[], date(3)), Some(Authority::Own));
+        assert_eq!(
+            authority(&steps[0], &request, "bob", false, &delegations, date(3)),
+            Some(Authority::Delegate("boss".to_string()))
+        );
+        assert_eq!(authority(&steps[0], &request, "bob", false, &delegations, date(6)), None);
+        assert_eq!(authority(&steps[0], &request, "other", true, &delegations, date(3)), None);
+
+        // Open steps go to any manager, but never to the requester
+        assert_eq!(authority(&steps[1], &request, "other", true, &[], date(3)), Some(Authority::Own));
+        assert_eq!(authority(&steps[1], &request, "bob", false, &[], date(3)), None);
+        assert_eq!(authority(&steps[1], &request_by("boss"), "boss", true, &[], date(3)), None);
+    }
+
+    #[test]
+    fn only_approvers_can_delegate() {
+        let users = users();
+        let by_id = crate::org::index_by_id(&users);
+        let rules = [rule(None, None, vec![Approver::User { user_id: "bob".to_string() }])];
+
+        assert!(can_approve("anyone", true, &by_id, &[]));
+        assert!(can_approve("boss", false, &by_id, &[]));
+        assert!(can_approve("bob", false, &by_id, &rules));
+        assert!(!can_approve("bob", false, &by_id, &[]));
+        assert!(!can_approve("ada", false, &by_id, &rules));
+    }
+
+    #[test]
+    fn keeps_only_delegations_held_today() {
+        let delegations = [
+            delegation("boss", "bob", date(1), date(5)),
+            delegation("boss", "ada", date(1), date(5)),
+            delegation("hr", "bob", date(6), date(9)),
+        ];
+        let held = delegations_held_by(&delegations, "bob", date(5));
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].delegator_id, "boss");
+    }
+}
@@ -65,6 +65,7 @@ pub async fn auth_middleware(
 }
 
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct AuthenticatedUser {
     pub id: String,
     pub email: String,
@@ -84,7 +85,8 @@ impl AuthenticatedUser {
         self.role == "coworker"
     }
 
-    pub fn can_view_all(&self, employee_id: &str) -> bool {
+    #[allow(dead_code)]
+    pub fn can_view_all(&self, _employee_id: &str) -> bool {
         // Manager can view all
         if self.is_manager() {
             return true;
@@ -98,7 +100,8 @@ impl AuthenticatedUser {
         false
     }
 
-    pub fn can_edit(&self, employee_id: &str) -> bool {
+    #[allow(dead_code)]
+    pub fn can_edit(&self, _employee_id: &str) -> bool {
         // Manager can edit all
         if self.is_manager() {
             return true;
@@ -0,0 +1,58 @@
+use std::io::Cursor;
+
+use image::{imageops::FilterType, ImageFormat, ImageReader, Limits};
+use thiserror::Error;
+
+/// Square thumbnail edge lengths generated for every upload, in pixels.
+pub const AVATAR_SIZES: [u32; 2] = [64, 256];
+
+/// Size served when the client does not ask for one.
+pub const DEFAULT_AVATAR_SIZE: u32 = 256;
+
+/// Largest source image we are willing to decode.
+const MAX_SOURCE_DIMENSION: u32 = 4096;
+
+#[derive(Debug, Error)]
+pub enum AvatarError {
+    #[error("unsupported image type, expected PNG, JPEG or WebP")]
+    UnsupportedType,
+    #[error("image could not be decoded: {0}")]
+    InvalidImage(#[from] image::ImageError),
+    #[error("image dimensions exceed {MAX_SOURCE_DIMENSION}x{MAX_SOURCE_DIMENSION}")]
+    TooLarge,
+}
+
+pub fn avatar_key(user_id: &str, size: u32) -> String {
+    format!("avatars/{}/{}.png", user_id, size)
+}
+
+/// Validates an uploaded image and renders the standard PNG thumbnails,
+/// center-cropped to a square. Returns `(size, png bytes)` pairs.
+pub fn render_thumbnails(bytes: &[u8]) -> Result<Vec<(u32, Vec<u8>)>, AvatarError> {
+    // Trust the content, not the declared content type
+    let format = image::guess_format(bytes).map_err(|_| AvatarError::UnsupportedType)?;
+    if !matches!(format, ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP) {
+        return Err(AvatarError::UnsupportedType);
+    }
+
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    reader.limits(limits);
+
+    let source = reader.decode().map_err(|err| match err {
+        image::ImageError::Limits(_) => AvatarError::TooLarge,
+        other => AvatarError::InvalidImage(other),
+    })?;
+
+    AVATAR_SIZES
+        .iter()
+        .map(|&size| {
+            let thumbnail = source.resize_to_fill(size, size, FilterType::Lanczos3);
+            let mut png = Vec::new();
+            thumbnail.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+            Ok((size, png))
+        })
+        .collect()
+}
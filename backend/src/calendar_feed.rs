@@ -0,0 +1,190 @@
+use chrono::{DateTime, Days, NaiveDate, Utc};
+
+use crate::models::{AbsenceRequest, AbsenceStatus, AbsenceType};
+
+/// Longest line allowed by RFC 5545, in octets, excluding the line break.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// One absence as it appears in a feed, dated in the absent user's time zone.
+pub struct FeedEvent<'a> {
+    pub absence: &'a AbsenceRequest,
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+    pub summary: String,
+}
+
+/// Absences a feed lists: approved ones, plus cancelled ones so calendar
+/// clients that already have them remove them.
+pub fn is_listed(status: &AbsenceStatus) -> bool {
+    matches!(
+        status,
+        AbsenceStatus::Approved | AbsenceStatus::CancellationRequested | AbsenceStatus::Cancelled
+    )
+}
+
+/// Renders absences as an iCalendar (RFC 5545) file of all-day events.
+/// Each request keeps its UID across fetches and bumps its `SEQUENCE` with
+/// every status change, so clients update events in place.
+pub fn render(name: &str, events: &[FeedEvent]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//NEWWORK//Absences//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+
+    for event in events {
+        let absence = event.absence;
+        let modified = absence
+            .history
+            .last()
+            .map(|change| change.changed_at)
+            .unwrap_or(absence.created_at);
+        let status = if absence.status == AbsenceStatus::Cancelled { "CANCELLED" } else { "CONFIRMED" };
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@newwork", absence.id));
+        lines.push(format!("DTSTAMP:{}", date_time(modified)));
+        lines.push(format!("LAST-MODIFIED:{}", date_time(modified)));
+        lines.push(format!("SEQUENCE:{}", absence.history.len()));
+        // All-day events end on the day after the last one
+        lines.push(format!("DTSTART;VALUE=DATE:{}", date(event.first)));
+        lines.push(format!("DTEND;VALUE=DATE:{}", date(event.last + Days::new(1))));
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        if let Some(description) = half_day_note(absence) {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+        lines.push(format!("STATUS:{}", status));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+pub fn type_label(absence_type: AbsenceType) -> &'static str {
+    match absence_type {
+        AbsenceType::Vacation => "Vacation",
+        AbsenceType::Sick => "Sick leave",
+        AbsenceType::Parental => "Parental leave",
+        AbsenceType::Unpaid => "Unpaid leave",
+        AbsenceType::Training => "Training",
+        AbsenceType::Remote => "Remote work",
+    }
+}
+
+fn half_day_note(absence: &AbsenceRequest) -> Option<&'static str> {
+    match (absence.start_half_day, absence.end_half_day) {
+        (true, true) => Some("Starts and ends at midday"),
+        (true, false) => Some("Starts at midday"),
+        (false, true) => Some("Ends at midday"),
+        (false, false) => None,
+    }
+}
+
+fn date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+fn date_time(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+/// Splits a content line into CRLF-terminated chunks of at most 75 octets,
+/// continuation lines starting with a space, without splitting characters.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AbsenceStatusChange;
+    use crate::test_support::{self, date};
+
+    /// Decided an hour after it was filed, starting at midday.
+    fn absence(status: AbsenceStatus) -> AbsenceRequest {
+        let absence = test_support::absence("ada", 8, 9, status.clone());
+        AbsenceRequest {
+            start_half_day: true,
+            history: vec![AbsenceStatusChange {
+                status,
+                changed_by: "boss".to_string(),
+                changed_at: absence.created_at + chrono::Duration::hours(1),
+                comment: None,
+            }],
+            ..absence
+        }
+    }
+
+    fn event(absence: &AbsenceRequest) -> FeedEvent<'_> {
+        FeedEvent {
+            absence,
+            first: date(8),
+            last: date(9),
+            summary: "Ada, Vacation".to_string(),
+        }
+    }
+
+    #[test]
+    fn renders_all_day_events() {
+        let approved = absence(AbsenceStatus::Approved);
+        let ics = render("Team; absences", &[event(&approved)]);
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        for expected in [
+            "X-WR-CALNAME:Team\\; absences",
+            "UID:ada-8@newwork",
+            "DTSTAMP:20240101T090000Z",
+            "SEQUENCE:1",
+            "DTSTART;VALUE=DATE:20240108",
+            "DTEND;VALUE=DATE:20240110",
+            "SUMMARY:Ada\\, Vacation",
+            "DESCRIPTION:Starts at midday",
+            "STATUS:CONFIRMED",
+        ] {
+            assert!(lines.contains(&expected), "missing {}", expected);
+        }
+    }
+
+    #[test]
+    fn marks_cancelled_absences() {
+        let cancelled = absence(AbsenceStatus::Cancelled);
+        assert!(render("Absences", &[event(&cancelled)]).contains("\r\nSTATUS:CANCELLED\r\n"));
+    }
+
+    #[test]
+    fn folds_long_lines_without_splitting_characters() {
+        assert_eq!(fold("short"), "short\r\n");
+
+        let line = "é".repeat(50);
+        let folded = fold(&line);
+        let chunks: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(chunks.iter().all(|c| c.len() <= MAX_LINE_OCTETS));
+        assert!(chunks[1..].iter().all(|c| c.starts_with(' ')));
+        assert_eq!(chunks.concat().replace(' ', ""), line);
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::models::AbsenceType;
+
+/// Runtime settings read from environment variables at startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Base URL of the frontend, used to build links in outgoing mail
+    pub app_base_url: String,
+    /// Directory where the file mailer drops outgoing messages
+    pub mail_outbox_dir: PathBuf,
+    /// How long an invitation token stays valid
+    pub invite_ttl_hours: i64,
+    /// Root directory of the local disk storage backend
+    pub storage_dir: PathBuf,
+    /// Maximum accepted avatar upload size in bytes
+    pub avatar_max_bytes: usize,
+    /// Only allow feedback between members of the same team
+    pub feedback_requires_shared_team: bool,
+    /// How long after posting authors may still edit their feedback
+    pub feedback_edit_window_minutes: i64,
+    /// Anonymous feedback about a user is withheld until this many
+    /// different people have left anonymous feedback for them
+    pub anonymous_feedback_min_authors: usize,
+    /// Review results about an employee are withheld until at least this
+    /// many reviewers have responded
+    pub review_min_responses: usize,
+    /// Deepest reply level allowed on data item feedback (top-level is 0)
+    pub feedback_reply_max_depth: usize,
+    /// How many days in the past an absence may start
+    pub absence_max_past_days: i64,
+    /// How many days ahead an absence may end
+    pub absence_max_future_days: i64,
+    /// Time zone for users without their own, used to date absences
+    pub default_time_zone: chrono_tz::Tz,
+    /// Fewest team members who must be present on a working day before
+    /// approving more leave needs an explicit override; 0 turns the check off
+    pub team_min_coverage: usize,
+    /// Yearly leave days per balance-based absence type
+    pub leave_entitlements: HashMap<AbsenceType, f64>,
+    /// Whether entitlement is credited monthly or all at once in January
+    pub leave_accrual: LeaveAccrual,
+    /// Unused days that may be carried into the next year
+    pub leave_carry_over_max_days: f64,
+    /// Months into the new year after which carried-over days expire
+    pub leave_carry_over_expiry_months: u32,
+    /// Text polishing provider used for feedback
+    pub polisher: PolisherKind,
+    /// Timeout for a single polishing call
+    pub polish_timeout_secs: u64,
+    /// Concurrent background polishing jobs
+    pub polish_workers: usize,
+    /// Attempts per job before it is marked failed
+    pub polish_max_attempts: u32,
+    /// Delay before the first retry, doubled on each further retry
+    pub polish_backoff_ms: u64,
+    /// How long a polish preview can be accepted after it was shown
+    pub polish_preview_ttl_minutes: i64,
+    pub huggingface_model_url: String,
+    pub huggingface_token: Option<String>,
+    /// Base URL of an OpenAI-compatible API, e.g. a local llama.cpp server
+    pub openai_base_url: String,
+    pub openai_api_key: Option<String>,
+    pub openai_model: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolisherKind {
+    HuggingFace,
+    OpenAi,
+    RuleBased,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LeaveAccrual {
+    Monthly,
+    Annual,
+}
+
+impl std::str::FromStr for LeaveAccrual {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "monthly" => Ok(Self::Monthly),
+            "annual" | "yearly" => Ok(Self::Annual),
+            other => Err(format!("unknown leave accrual '{}'", other)),
+        }
+    }
+}
+
+impl std::str::FromStr for PolisherKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "huggingface" => Ok(Self::HuggingFace),
+            "openai" => Ok(Self::OpenAi),
+            "rule-based" | "rules" | "offline" => Ok(Self::RuleBased),
+            other => Err(format!("unknown polisher '{}'", other)),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Self {
+            app_base_url: env_or("APP_BASE_URL", "http://localhost:5173".to_string()),
+            mail_outbox_dir: PathBuf::from(env_or("MAIL_OUTBOX_DIR", "mail_outbox".to_string())),
+            invite_ttl_hours: env_or("INVITE_TTL_HOURS", 72),
+            storage_dir: PathBuf::from(env_or("STORAGE_DIR", "storage".to_string())),
+            avatar_max_bytes: env_or("AVATAR_MAX_BYTES", 5 * 1024 * 1024),
+            feedback_requires_shared_team: env_or("FEEDBACK_REQUIRE_SHARED_TEAM", false),
+            feedback_edit_window_minutes: env_or("FEEDBACK_EDIT_WINDOW_MINUTES", 30),
+            anonymous_feedback_min_authors: env_or("ANONYMOUS_FEEDBACK_MIN_AUTHORS", 3),
+            review_min_responses: env_or("REVIEW_MIN_RESPONSES", 3),
+            feedback_reply_max_depth: env_or("FEEDBACK_REPLY_MAX_DEPTH", 3),
+            absence_max_past_days: env_or("ABSENCE_MAX_PAST_DAYS", 30),
+            absence_max_future_days: env_or("ABSENCE_MAX_FUTURE_DAYS", 365),
+            default_time_zone: env_or("DEFAULT_TIME_ZONE", chrono_tz::UTC),
+            team_min_coverage: env_or("TEAM_MIN_COVERAGE", 0),
+            leave_entitlements: parse_entitlements(&env_or(
+                "LEAVE_ENTITLEMENTS",
+                "vacation=25,training=5".to_string(),
+            ))
+            .unwrap_or_else(|err| panic!("invalid value for LEAVE_ENTITLEMENTS: {}", err)),
+            leave_accrual: env_or("LEAVE_ACCRUAL", LeaveAccrual::Monthly),
+            leave_carry_over_max_days: env_or("LEAVE_CARRY_OVER_MAX_DAYS", 5.0),
+            leave_carry_over_expiry_months: env_or("LEAVE_CARRY_OVER_EXPIRY_MONTHS", 3),
+            polisher: env_or("POLISHER", PolisherKind::HuggingFace),
+            polish_timeout_secs: env_or("POLISH_TIMEOUT_SECS", 20),
+            polish_workers: env_or("POLISH_WORKERS", 2),
+            polish_max_attempts: env_or("POLISH_MAX_ATTEMPTS", 3),
+            polish_backoff_ms: env_or("POLISH_BACKOFF_MS", 1000),
+            polish_preview_ttl_minutes: env_or("POLISH_PREVIEW_TTL_MINUTES", 30),
+            huggingface_model_url: env_or(
+                "HUGGINGFACE_MODEL_URL",
+                "https://api-inference.huggingface.co/models/gpt2".to_string(),
+            ),
+            huggingface_token: std::env::var("HUGGINGFACE_TOKEN").ok(),
+            openai_base_url: env_or("OPENAI_BASE_URL", "https://api.openai.com/v1".to_string()),
+            openai_api_key: std::env::var("OPENAI_API_KEY").ok(),
+            openai_model: env_or("OPENAI_MODEL", "gpt-4o-mini".to_string()),
+        }
+    }
+}
+
+/// Parses `type=days` pairs such as `vacation=25,training=5`.
+fn parse_entitlements(value: &str) -> Result<HashMap<AbsenceType, f64>, String> {
+    value
+        .split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (kind, days) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("'{}' is not of the form type=days", pair.trim()))?;
+            let kind = serde_json::from_value(serde_json::Value::String(kind.trim().to_lowercase()))
+                .map_err(|_| format!("unknown absence type '{}'", kind.trim()))?;
+            let days = days
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|d| d.is_finite() && *d >= 0.0)
+                .ok_or_else(|| format!("'{}' is not a number of days", days.trim()))?;
+            Ok((kind, days))
+        })
+        .collect()
+}
+
+/// Reads `key`, using `default` when it is unset or empty. A value that is
+/// set but does not parse stops startup rather than being ignored.
+fn env_or<T>(key: &str, default: T) -> T
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    match std::env::var(key) {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse()
+            .unwrap_or_else(|err| panic!("invalid value for {}: {}", key, err)),
+        _ => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entitlements_or_says_what_is_wrong() {
+        let parsed = parse_entitlements("vacation=25, Training = 5.5,").unwrap();
+        assert_eq!(parsed, HashMap::from([(AbsenceType::Vacation, 25.0), (AbsenceType::Training, 5.5)]));
+
+        assert!(parse_entitlements("vacation").is_err());
+        assert!(parse_entitlements("holiday=5").is_err());
+        assert!(parse_entitlements("vacation=lots").is_err());
+        assert!(parse_entitlements("vacation=-1").is_err());
+    }
+}
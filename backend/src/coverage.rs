@@ -0,0 +1,104 @@
+use chrono::{Datelike, NaiveDate};
+use chrono_tz::Tz;
+
+use crate::absence;
+use crate::models::{AbsenceRequest, AbsenceStatus, CoverageConflict, User};
+
+/// Approved absences (including ones with a pending cancellation) take a
+/// person out of the headcount; pending requests do not yet.
+pub fn counts_as_absent(status: &AbsenceStatus) -> bool {
+    matches!(status, AbsenceStatus::Approved | AbsenceStatus::CancellationRequested)
+}
+
+/// Whether `absence` covers `date` in the absent user's time zone.
+pub fn covers(absence: &AbsenceRequest, time_zone: Tz, date: NaiveDate) -> bool {
+    let (first, last) = absence::local_dates(absence, time_zone);
+    first <= date && date <= last
+}
+
+/// Working days of `candidate` (by its owner's schedule) on which
+/// approving it would leave fewer than `minimum` team members present.
+pub fn conflicts(
+    candidate: &AbsenceRequest,
+    team: &[&User],
+    absences: &[AbsenceRequest],
+    default_time_zone: Tz,
+    minimum: usize,
+) -> Vec<CoverageConflict> {
+    let Some(requester) = team.iter().find(|u| u.id == candidate.user_id) else {
+        return Vec::new();
+    };
+    let (first, last) = absence::local_dates(candidate, absence::time_zone_of(requester, default_time_zone));
+
+    first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .filter(|date| requester.work_schedule.day(date.weekday()) > 0.0)
+        .filter_map(|date| {
+            let absent_user_ids: Vec<String> = team
+                .iter()
+                .filter(|member| {
+                    member.id == candidate.user_id
+                        || absences.iter().any(|a| {
+                            a.user_id == member.id
+                                && counts_as_absent(&a.status)
+                                && covers(a, absence::time_zone_of(member, default_time_zone), date)
+                        })
+                })
+                .map(|member| member.id.clone())
+                .collect();
+            let present = team.len() - absent_user_ids.len();
+
+            (present < minimum).then_some(CoverageConflict {
+                date,
+                present,
+                minimum,
+                absent_user_ids,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{absence, date, user};
+
+    #[test]
+    fn flags_days_below_the_minimum() {
+        let (ada, bob, cy) = (user("ada"), user("bob"), user("cy"));
+        let team = [&ada, &bob, &cy];
+        let candidate = absence("ada", 1, 3, AbsenceStatus::Pending);
+        let absences = [absence("bob", 2, 2, AbsenceStatus::Approved)];
+
+        let conflicts = conflicts(&candidate, &team, &absences, chrono_tz::UTC, 2);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].date, date(2));
+        assert_eq!(conflicts[0].present, 1);
+        assert_eq!(conflicts[0].absent_user_ids, ["ada", "bob"]);
+    }
+
+    #[test]
+    fn ignores_pending_and_rejected_absences_and_days_off() {
+        let (ada, bob) = (user("ada"), user("bob"));
+        let team = [&ada, &bob];
+        // Friday to Monday: the weekend is not a working day
+        let candidate = absence("ada", 5, 8, AbsenceStatus::Pending);
+        let absences = [
+            absence("bob", 5, 5, AbsenceStatus::Pending),
+            absence("bob", 8, 8, AbsenceStatus::Rejected),
+            absence("bob", 6, 7, AbsenceStatus::Approved),
+        ];
+
+        assert!(conflicts(&candidate, &team, &absences, chrono_tz::UTC, 1).is_empty());
+        let all_days = conflicts(&candidate, &team, &[], chrono_tz::UTC, 2);
+        assert_eq!(all_days.iter().map(|c| c.date).collect::<Vec<_>>(), [date(5), date(8)]);
+    }
+
+    #[test]
+    fn a_minimum_of_zero_never_conflicts() {
+        let ada = user("ada");
+        let candidate = absence("ada", 1, 5, AbsenceStatus::Pending);
+        assert!(conflicts(&candidate, &[&ada], &[], chrono_tz::UTC, 0).is_empty());
+    }
+}
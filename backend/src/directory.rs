@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::models::{CreateUserRequest, ImportRowError, User, UserStatus};
+
+pub const VALID_ROLES: [&str; 3] = ["manager", "employee", "coworker"];
+
+/// Users are keyed by email, so every email is stored and looked up in
+/// this form.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// A loose shape check; the invitation mail is what proves the address.
+pub fn is_valid_email(email: &str) -> bool {
+    let email = email.trim();
+    !email.is_empty() && email.contains('@') && !email.contains(char::is_whitespace)
+}
+
+/// Field-level checks shared by `create_user` and the CSV import.
+pub fn validate_create_user(req: &CreateUserRequest) -> Result<(), String> {
+    if req.name.trim().is_empty() {
+        return Err("name is required".to_string());
+    }
+    if !is_valid_email(&req.email) {
+        return Err(format!("invalid email '{}'", req.email));
+    }
+    if !VALID_ROLES.contains(&req.role.as_str()) {
+        return Err(format!("invalid role '{}'", req.role));
+    }
+    Ok(())
+}
+
+/// A line of the directory export. Its columns are a superset of the
+/// import's, so an export can be imported again.
+#[derive(Debug, Serialize)]
+pub struct ExportRow<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub email: &'a str,
+    pub role: &'a str,
+    pub manager_id: Option<&'a str>,
+    pub status: UserStatus,
+}
+
+impl<'a> From<&'a User> for ExportRow<'a> {
+    fn from(user: &'a User) -> Self {
+        Self {
+            id: &user.id,
+            name: &user.name,
+            email: &user.email,
+            role: &user.role,
+            manager_id: user.manager_id.as_deref(),
+            status: user.status,
+        }
+    }
+}
+
+/// Rows of an import file, split into those that can be created and
+/// per-row errors.
+pub struct ImportCheck {
+    pub valid_rows: Vec<CreateUserRequest>,
+    pub errors: Vec<ImportRowError>,
+    pub total_rows: usize,
+}
+
+/// Parses an import CSV and validates every row against `users` and the
+/// rows before it. Emails are normalized before any comparison.
+pub fn check_import(csv_bytes: &[u8], users: &HashMap<String, User>) -> ImportCheck {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv_bytes);
+
+    let mut seen_emails = HashSet::new();
+    let mut valid_rows = Vec::new();
+    let mut errors = Vec::new();
+    let mut total_rows = 0;
+
+    for (index, record) in reader.deserialize::<CreateUserRequest>().enumerate() {
+        let row = index + 1;
+        total_rows += 1;
+
+        let create_req = match record {
+            Ok(create_req) => CreateUserRequest { email: normalize_email(&create_req.email), ..create_req },
+            Err(err) => {
+                errors.push(ImportRowError { row, email: None, error: err.to_string() });
+                continue;
+            }
+        };
+
+        let error = if let Err(err) = validate_create_user(&create_req) {
+            Some(err)
+        } else if users.contains_key(&create_req.email) {
+            Some("a user with this email already exists".to_string())
+        } else if create_req
+            .manager_id
+            .as_ref()
+            .is_some_and(|m| !users.values().any(|u| &u.id == m))
+        {
+            Some("manager_id does not match an existing user".to_string())
+        } else if !seen_emails.insert(create_req.email.clone()) {
+            Some("duplicate email within the file".to_string())
+        } else {
+            None
+        };
+
+        match error {
+            Some(error) => errors.push(ImportRowError {
+                row,
+                email: Some(create_req.email),
+                error,
+            }),
+            None => valid_rows.push(create_req),
+        }
+    }
+
+    ImportCheck { valid_rows, errors, total_rows }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{UserStatus, WorkSchedule};
+
+    fn existing(email: &str) -> HashMap<String, User> {
+        let user = User {
+            id: "1".to_string(),
+            name: "Existing".to_string(),
+            email: email.to_string(),
+            password_hash: String::new(),
+            role: "manager".to_string(),
+            manager_id: None,
+            status: UserStatus::Active,
+            avatar_version: None,
+            location: None,
+            time_zone: None,
+            work_schedule: WorkSchedule::default(),
+        };
+        HashMap::from([(email.to_string(), user)])
+    }
+
+    #[test]
+    fn accepts_valid_rows_and_normalizes_emails() {
+        let csv = "name,email,role,manager_id\nAda, Ada@Example.com ,employee,1\n";
+        let check = check_import(csv.as_bytes(), &existing("boss@example.com"));
+
+        assert_eq!(check.total_rows, 1);
+        assert!(check.errors.is_empty());
+        assert_eq!(check.valid_rows[0].email, "ada@example.com");
+    }
+
+    #[test]
+    fn rejects_existing_emails_regardless_of_case() {
+        let csv = "name,email,role\nBoss,BOSS@example.com,employee\n";
+        let check = check_import(csv.as_bytes(), &existing("boss@example.com"));
+
+        assert!(check.valid_rows.is_empty());
+        assert_eq!(check.errors[0].error, "a user with this email already exists");
+    }
+
+    #[test]
+    fn reports_duplicates_within_the_file() {
+        let csv = "name,email,role\nA,a@example.com,employee\nB,A@EXAMPLE.COM,coworker\n";
+        let check = check_import(csv.as_bytes(), &HashMap::new());
+
+        assert_eq!(check.valid_rows.len(), 1);
+        assert_eq!(check.errors[0].row, 2);
+        assert_eq!(check.errors[0].error, "duplicate email within the file");
+    }
+
+    #[test]
+    fn reports_invalid_fields_and_unknown_managers() {
+        let csv = "name,email,role,manager_id\n,a@example.com,employee,\nB,b@example.com,boss,\nC,c@example.com,employee,42\nD,not-an-email,employee,\n";
+        let check = check_import(csv.as_bytes(), &HashMap::new());
+
+        let errors: Vec<&str> = check.errors.iter().map(|e| e.error.as_str()).collect();
+        assert_eq!(
+            errors,
+            [
+                "name is required",
+                "invalid role 'boss'",
+                "manager_id does not match an existing user",
+                "invalid email 'not-an-email'",
+            ]
+        );
+        assert_eq!(check.total_rows, 4);
+    }
+
+    #[test]
+    fn reports_malformed_rows() {
+        let csv = "name,email\nA,a@example.com\n";
+        let check = check_import(csv.as_bytes(), &HashMap::new());
+
+        assert!(check.valid_rows.is_empty());
+        assert_eq!(check.errors[0].email, None);
+    }
+}
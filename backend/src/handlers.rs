@@ -1,13 +1,30 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
     Extension,
 };
 use serde_json::{json, Value};
 use uuid::Uuid;
 
+use crate::absence;
+use crate::approval;
+use crate::coverage;
+use crate::directory::{self, ImportCheck};
 use crate::auth::{AuthenticatedUser, Claims, generate_token};
+use crate::calendar_feed;
+use crate::avatar::{avatar_key, render_thumbnails, AvatarError, AVATAR_SIZES, DEFAULT_AVATAR_SIZE};
+use crate::mailer::MailMessage;
+use crate::org;
+use crate::payroll;
+use crate::holidays;
+use crate::leave;
+use crate::jobs::{PolishJob, PolishTarget};
+use crate::polish::{polish_checked, word_diff};
+use crate::review;
+use crate::seal::{self, AuthorSealer};
+use crate::templates;
+use crate::tokens;
 use crate::models::*;
 use crate::state::AppState;
 
@@ -17,9 +34,14 @@ pub async fn login(
 ) -> Result<Json<LoginResponse>, StatusCode> {
     let users = state.users.read().await;
     let user = users
-        .get(&login_req.email)
+        .get(&directory::normalize_email(&login_req.email))
         .ok_or(StatusCode::UNAUTHORIZED)?;
 
+    // Invited users have no password until they accept their invitation
+    if user.status != UserStatus::Active {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
     // In production, use proper password hashing/verification
     // For demo, we'll just check a simple hash
     let valid = bcrypt::verify(&login_req.password, &user.password_hash)
@@ -34,12 +56,7 @@ pub async fn login(
 
     Ok(Json(LoginResponse {
         token,
-        user: UserInfo {
-            id: user.id.clone(),
-            name: user.name.clone(),
-            email: user.email.clone(),
-            role: user.role.clone(),
-        },
+        user: UserInfo::from(user),
     }))
 }
 
@@ -65,12 +82,7 @@ pub async fn get_user(
         .find(|u| u.id == user_id)
         .ok_or(StatusCode::NOT_FOUND)?;
 
-    Ok(Json(json!(UserInfo {
-        id: user.id.clone(),
-        name: user.name.clone(),
-        email: user.email.clone(),
-        role: user.role.clone(),
-    })))
+    Ok(Json(json!(UserInfo::from(user))))
 }
 
 pub async fn update_user(
@@ -104,44 +116,69 @@ pub async fn update_user(
         user_to_update.name = name;
     }
     if let Some(email) = &update_req.email {
-        user_to_update.email = email.clone();
+        if !directory::is_valid_email(email) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        let email = directory::normalize_email(email);
+        // The email is the user's key, so it may not belong to anyone else
+        if users.get(&email).is_some_and(|u| u.id != user_id) {
+            return Err(StatusCode::CONFLICT);
+        }
+        user_to_update.email = email;
     }
     if let Some(role) = &update_req.role {
-        if !["manager", "employee", "coworker"].contains(&role.as_str()) {
+        if !directory::VALID_ROLES.contains(&role.as_str()) {
             return Err(StatusCode::BAD_REQUEST);
         }
         user_to_update.role = role.clone();
     }
-
-    // If email changed, update the HashMap key
-    if let Some(new_email) = update_req.email {
-        if old_email != new_email {
-            users.remove(&old_email);
+    if let Some(manager_id) = update_req.manager_id {
+        if manager_id.is_empty() {
+            user_to_update.manager_id = None;
+        } else {
+            let by_id = org::index_by_id(&users);
+            if !by_id.contains_key(manager_id.as_str()) {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            // Reject changes that would make someone their own (indirect) manager
+            if org::would_create_cycle(&by_id, &user_id, &manager_id) {
+                return Err(StatusCode::CONFLICT);
+            }
+            user_to_update.manager_id = Some(manager_id);
         }
-        users.insert(new_email.clone(), user_to_update.clone());
-        Ok(Json(json!(UserInfo {
-            id: user_to_update.id.clone(),
-            name: user_to_update.name.clone(),
-            email: user_to_update.email.clone(),
-            role: user_to_update.role.clone(),
-        })))
-    } else {
-        // Email didn't change, just update in place
-        users.insert(old_email.clone(), user_to_update.clone());
-        Ok(Json(json!(UserInfo {
-            id: user_to_update.id.clone(),
-            name: user_to_update.name.clone(),
-            email: user_to_update.email.clone(),
-            role: user_to_update.role.clone(),
-        })))
     }
+    if let Some(location) = update_req.location {
+        let location = location.trim().to_string();
+        user_to_update.location = (!location.is_empty()).then_some(location);
+    }
+    if let Some(time_zone) = update_req.time_zone {
+        if time_zone.is_empty() {
+            user_to_update.time_zone = None;
+        } else {
+            if time_zone.parse::<chrono_tz::Tz>().is_err() {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            user_to_update.time_zone = Some(time_zone);
+        }
+    }
+    if let Some(work_schedule) = update_req.work_schedule {
+        if !work_schedule.is_valid() {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        user_to_update.work_schedule = work_schedule;
+    }
+
+    // Re-key the user under the (normalized) email if it changed
+    users.remove(&old_email);
+    users.insert(user_to_update.email.clone(), user_to_update.clone());
+    Ok(Json(json!(UserInfo::from(&user_to_update))))
 }
 
 pub async fn create_feedback(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
     Json(feedback_req): Json<CreateFeedbackRequest>,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Json<Value>, ErrorWithBody> {
     // Check if user is coworker or employee
     let auth_user = AuthenticatedUser {
         id: claims.sub.clone(),
@@ -150,147 +187,2614 @@ pub async fn create_feedback(
     };
 
     if !auth_user.is_coworker() && !auth_user.is_employee() {
-        return Err(StatusCode::FORBIDDEN);
+        return Err(error_with_body(StatusCode::FORBIDDEN, "only employees and co-workers can leave feedback"));
     }
 
-    let mut content = feedback_req.content.clone();
-    
-    // Polish content using AI if requested
-    if feedback_req.polish.unwrap_or(false) {
-        content = polish_feedback_with_ai(&content).await
-            .unwrap_or_else(|_| feedback_req.content.clone());
+    // Nobody writes feedback about themselves
+    if feedback_req.user_id == auth_user.id {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "you cannot leave feedback about yourself"));
+    }
+
+    // The recipient must be an existing, active user
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
+    let recipient = by_id
+        .get(feedback_req.user_id.as_str())
+        .filter(|u| u.status == UserStatus::Active)
+        .ok_or_else(|| error_with_body(StatusCode::BAD_REQUEST, "user_id does not match an active user"))?;
+    if state.config.feedback_requires_shared_team
+        && !org::same_team(&by_id, &auth_user.id, &recipient.id)
+    {
+        return Err(error_with_body(StatusCode::FORBIDDEN, "feedback is limited to members of your team"));
+    }
+    drop(users);
+
+    // Structured answers must follow an active template; free-form
+    // feedback needs some text
+    let has_content = !feedback_req.content.trim().is_empty();
+    if let Some(template_id) = &feedback_req.template_id {
+        let templates = state.feedback_templates.read().await;
+        let template = templates
+            .get(template_id)
+            .filter(|t| !t.archived)
+            .ok_or_else(|| error_with_body(StatusCode::BAD_REQUEST, "template_id does not match an active template"))?;
+        templates::validate_answers(template.questions(), &feedback_req.answers)
+            .map_err(|err| error_with_body(StatusCode::BAD_REQUEST, &err))?;
+    } else if !has_content {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "content is required"));
+    } else if !feedback_req.answers.is_empty() {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "answers need a template_id"));
     }
 
+    // Only the free-text part can be polished
+    if !has_content && (feedback_req.polish == Some(true) || feedback_req.preview_id.is_some()) {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "only feedback with content can be polished"));
+    }
+
+    let suggestion = redeem_preview(&state, &claims.sub, feedback_req.preview_id, &feedback_req.content)
+        .await
+        .map_err(|status| error_with_body(status, "preview_id does not match a polish preview of this content"))?;
+    let (polish_status, polished_content, published_version) =
+        resolve_polish_choice(feedback_req.polish, suggestion, feedback_req.publish)
+            .map_err(|status| error_with_body(status, "publishing the polished version needs polish or a preview_id"))?;
+
+    // Anonymous authors are only stored sealed
+    let anonymous = feedback_req.anonymous.unwrap_or(false);
+    let (from_user_id, sealed_author) = if anonymous {
+        (None, Some(state.sealer.seal(&claims.sub)))
+    } else {
+        (Some(claims.sub), None)
+    };
+
     let feedback = Feedback {
         id: Uuid::new_v4().to_string(),
         user_id: feedback_req.user_id,
-        from_user_id: claims.sub,
+        from_user_id,
+        anonymous,
+        sealed_author,
         content: feedback_req.content,
-        polished_content: if feedback_req.polish.unwrap_or(false) {
-            Some(content)
-        } else {
-            None
-        },
+        template_id: feedback_req.template_id,
+        answers: feedback_req.answers,
+        polished_content,
+        polish_status,
+        polish_error: None,
+        published_version,
         created_at: chrono::Utc::now(),
+        updated_at: None,
+        revisions: vec![],
+        retracted_at: None,
+        moderation: None,
     };
 
     let mut feedbacks = state.feedbacks.write().await;
     feedbacks.push(feedback.clone());
+    drop(feedbacks);
+
+    if polish_status == PolishStatus::Pending {
+        state.polish_queue.enqueue(PolishJob {
+            target: PolishTarget::Feedback {
+                feedback_id: feedback.id.clone(),
+            },
+            content: feedback.content.clone(),
+        });
+    }
 
     Ok(Json(json!(feedback)))
 }
 
-async fn polish_feedback_with_ai(content: &str) -> Result<String, reqwest::Error> {
-    // Using HuggingFace Inference API - free tier
-    // Note: For production, set HUGGINGFACE_TOKEN environment variable
-    let token = std::env::var("HUGGINGFACE_TOKEN").ok();
-    
-    let client = reqwest::Client::new();
-    let prompt = format!(
-        "Please polish and improve the following professional feedback while maintaining its original meaning: {}",
-        content
+/// Takes the suggestion of a preview shown to `author_id` for exactly
+/// `content`. Previews can be accepted once, until they expire.
+async fn redeem_preview(
+    state: &AppState,
+    author_id: &str,
+    preview_id: Option<String>,
+    content: &str,
+) -> Result<Option<String>, StatusCode> {
+    let Some(preview_id) = preview_id else {
+        return Ok(None);
+    };
+    let mut previews = state.polish_previews.write().await;
+    let preview = previews.get(&preview_id).ok_or(StatusCode::BAD_REQUEST)?;
+    if preview.author_id != author_id || preview.original != content {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let preview = previews.remove(&preview_id).ok_or(StatusCode::BAD_REQUEST)?;
+    if chrono::Utc::now() - preview.created_at > chrono::Duration::minutes(state.config.polish_preview_ttl_minutes) {
+        return Err(StatusCode::GONE);
+    }
+    Ok(Some(preview.suggested))
+}
+
+/// Works out how a new feedback entry is polished and which version is
+/// published: an accepted preview suggestion is stored as is, `polish`
+/// queues a background job, and otherwise the original is published.
+fn resolve_polish_choice(
+    polish: Option<bool>,
+    polished_content: Option<String>,
+    publish: Option<PublishedVersion>,
+) -> Result<(PolishStatus, Option<String>, PublishedVersion), StatusCode> {
+    let status = if polished_content.is_some() {
+        PolishStatus::Succeeded
+    } else if polish.unwrap_or(false) {
+        PolishStatus::Pending
+    } else {
+        PolishStatus::Skipped
+    };
+
+    let published_version = match (publish, status) {
+        (Some(PublishedVersion::Polished), PolishStatus::Skipped) => return Err(StatusCode::BAD_REQUEST),
+        (Some(version), _) => version,
+        (None, PolishStatus::Skipped) => PublishedVersion::Original,
+        (None, _) => PublishedVersion::Polished,
+    };
+
+    Ok((status, polished_content, published_version))
+}
+
+pub async fn polish_preview(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(preview_req): Json<PolishPreviewRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if preview_req.content.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Previews are interactive, so polish inline instead of queueing a job
+    let timeout = std::time::Duration::from_secs(state.config.polish_timeout_secs);
+    let suggested = tokio::time::timeout(
+        timeout,
+        polish_checked(state.polisher.as_ref(), &preview_req.content),
+    )
+    .await
+    .map_err(|_| StatusCode::GATEWAY_TIMEOUT)?
+    .map_err(|err| {
+        tracing::warn!("{} polisher failed: {}", state.polisher.name(), err);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    // Remember what was suggested, so creating feedback can only accept it
+    let now = chrono::Utc::now();
+    let ttl = chrono::Duration::minutes(state.config.polish_preview_ttl_minutes);
+    let preview_id = Uuid::new_v4().to_string();
+    let mut previews = state.polish_previews.write().await;
+    previews.retain(|_, preview| now - preview.created_at <= ttl);
+    previews.insert(
+        preview_id.clone(),
+        PolishPreview {
+            author_id: claims.sub,
+            original: preview_req.content.clone(),
+            suggested: suggested.clone(),
+            created_at: now,
+        },
+    );
+    drop(previews);
+
+    Ok(Json(json!(PolishPreviewResponse {
+        preview_id,
+        diff: word_diff(&preview_req.content, &suggested),
+        original: preview_req.content,
+        suggested,
+        provider: state.polisher.name().to_string(),
+    })))
+}
+
+/// Feedback about a user is visible to that user and anyone in their management chain.
+fn can_read_feedback_about(users: &std::collections::HashMap<String, User>, reader_id: &str, subject_id: &str) -> bool {
+    if reader_id == subject_id {
+        return true;
+    }
+    let by_id = org::index_by_id(users);
+    org::management_chain(&by_id, subject_id)
+        .iter()
+        .any(|manager| manager.id == reader_id)
+}
+
+/// Drops retracted/hidden entries unless requested, applies the date
+/// window, sorts newest first and paginates.
+fn page_feedback(mut list: Vec<Feedback>, query: &FeedbackListQuery, include_hidden: bool) -> Paginated<Feedback> {
+    list.retain(|f| {
+        (include_hidden || f.is_visible())
+            && query.from.is_none_or(|from| f.created_at >= from)
+            && query.to.is_none_or(|to| f.created_at < to)
+    });
+    list.sort_by_key(|f| std::cmp::Reverse(f.created_at));
+    Paginated::from_vec(list, query.page, query.per_page)
+}
+
+pub async fn list_user_feedback(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<String>,
+    Query(query): Query<FeedbackListQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let users = state.users.read().await;
+    if !users.values().any(|u| u.id == user_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if !can_read_feedback_about(&users, &claims.sub, &user_id) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    drop(users);
+
+    let feedbacks = state.feedbacks.read().await;
+
+    // Anonymous entries are withheld until enough people wrote one, so a
+    // single anonymous author cannot be singled out
+    let anonymous_released = seal::anonymous_released(
+        &feedbacks,
+        &user_id,
+        &state.sealer,
+        state.config.anonymous_feedback_min_authors,
     );
+    let received: Vec<Feedback> = feedbacks
+        .iter()
+        .filter(|f| f.user_id == user_id && (anonymous_released || !f.anonymous))
+        .map(|f| reader_view(f, &claims.sub, &state.sealer))
+        .collect();
 
-    // Using a free text generation model from HuggingFace
-    // Note: This is a simplified example - you'd want to use a proper text polish/grammar model
-    let mut request = client
-        .post("https://api-inference.huggingface.co/models/gpt2")
-        .json(&json!({
-            "inputs": prompt,
-            "parameters": {
-                "max_length": 200,
-                "temperature": 0.7
-            }
-        }));
-    
-    if let Some(t) = token {
-        request = request.header("Authorization", format!("Bearer {}", t));
+    let include_hidden = include_hidden(query.include_hidden, &claims.role);
+
+    Ok(Json(json!(page_feedback(received, &query, include_hidden))))
+}
+
+pub async fn list_authored_feedback(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<FeedbackListQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let feedbacks = state.feedbacks.read().await;
+    let authored: Vec<Feedback> = feedbacks
+        .iter()
+        .filter(|f| f.is_written_by(&claims.sub, &state.sealer))
+        .cloned()
+        .collect();
+
+    Ok(Json(json!(page_feedback(authored, &query, false))))
+}
+
+pub async fn get_feedback(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let feedbacks = state.feedbacks.read().await;
+    let feedback = feedbacks
+        .iter()
+        .find(|f| f.id == id)
+        .cloned()
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let anonymous_released = !feedback.anonymous
+        || seal::anonymous_released(
+            &feedbacks,
+            &feedback.user_id,
+            &state.sealer,
+            state.config.anonymous_feedback_min_authors,
+        );
+    drop(feedbacks);
+
+    // Managers can still open retracted and hidden entries
+    if !feedback.is_visible() && claims.role != "manager" {
+        return Err(StatusCode::NOT_FOUND);
     }
-    
-    let response = request.send().await?;
 
-    // For demo purposes, if API fails, return improved version manually
-    if !response.status().is_success() {
-        // Fallback: simple improvement
-        return Ok(format!("[AI-Polished] {}", content.trim()));
-    }
+    // Authors can always see what they wrote
+    if !feedback.is_written_by(&claims.sub, &state.sealer) {
+        if !anonymous_released {
+            return Err(StatusCode::NOT_FOUND);
+        }
+        let users = state.users.read().await;
+        if !can_read_feedback_about(&users, &claims.sub, &feedback.user_id) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    Ok(Json(json!(reader_view(&feedback, &claims.sub, &state.sealer))))
+}
+
+/// Copy of a feedback entry as `reader_id` may see it: authors get every
+/// version, everyone else only the published text.
+fn reader_view(feedback: &Feedback, reader_id: &str, sealer: &AuthorSealer) -> Feedback {
+    let mut view = feedback.clone();
+    if !feedback.is_written_by(reader_id, sealer) {
+        view.publish_only();
+    }
+    view
+}
+
+/// Retracted and hidden entries stay available to managers for audit,
+/// when they ask for them.
+fn include_hidden(requested: Option<bool>, role: &str) -> bool {
+    requested.unwrap_or(false) && role == "manager"
+}
+
+/// Authors may edit their own feedback while it is visible and still
+/// inside the configured edit window.
+fn edit_feedback(
+    feedback: &mut impl Moderated,
+    is_author: bool,
+    content: String,
+    edit_window_minutes: i64,
+) -> Result<(), StatusCode> {
+    if !is_author {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if !feedback.is_visible() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let now = chrono::Utc::now();
+    if now - feedback.created_at() > chrono::Duration::minutes(edit_window_minutes) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    feedback.revise(content, now);
+    Ok(())
+}
+
+/// Only the author can retract, and only what is still visible.
+fn retract(feedback: &mut impl Moderated, is_author: bool) -> Result<(), StatusCode> {
+    if !is_author {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if !feedback.is_visible() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    *feedback.retracted_at_mut() = Some(chrono::Utc::now());
+    Ok(())
+}
+
+/// Checks a hide request before any feedback is looked up.
+fn moderation_from(claims: &Claims, hide_req: HideFeedbackRequest) -> Result<FeedbackModeration, StatusCode> {
+    // Only managers can moderate feedback
+    if claims.role != "manager" {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if hide_req.reason.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(FeedbackModeration {
+        hidden_by: claims.sub.clone(),
+        reason: hide_req.reason,
+        hidden_at: chrono::Utc::now(),
+    })
+}
+
+fn hide(feedback: &mut impl Moderated, moderation: FeedbackModeration) -> Result<(), StatusCode> {
+    if feedback.is_hidden() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    *feedback.moderation_mut() = Some(moderation);
+    Ok(())
+}
+
+pub async fn update_feedback(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(update_req): Json<UpdateFeedbackRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if update_req.content.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut feedbacks = state.feedbacks.write().await;
+    let feedback = feedbacks
+        .iter_mut()
+        .find(|f| f.id == id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let is_author = feedback.is_written_by(&claims.sub, &state.sealer);
+    edit_feedback(feedback, is_author, update_req.content, state.config.feedback_edit_window_minutes)?;
+
+    Ok(Json(json!(feedback)))
+}
+
+pub async fn retract_feedback(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let mut feedbacks = state.feedbacks.write().await;
+    let feedback = feedbacks
+        .iter_mut()
+        .find(|f| f.id == id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let is_author = feedback.is_written_by(&claims.sub, &state.sealer);
+    retract(feedback, is_author)?;
+
+    Ok(Json(json!(feedback)))
+}
+
+pub async fn hide_feedback(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(hide_req): Json<HideFeedbackRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let moderation = moderation_from(&claims, hide_req)?;
+
+    let mut feedbacks = state.feedbacks.write().await;
+    let feedback = feedbacks
+        .iter_mut()
+        .find(|f| f.id == id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    hide(feedback, moderation)?;
+
+    Ok(Json(json!(reader_view(feedback, &claims.sub, &state.sealer))))
+}
+
+pub async fn create_feedback_template(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(template_req): Json<CreateFeedbackTemplateRequest>,
+) -> Result<Json<Value>, ErrorWithBody> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub.clone(),
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can define feedback templates
+    if !auth_user.is_manager() {
+        return Err(error_with_body(StatusCode::FORBIDDEN, "only managers can define feedback templates"));
+    }
+
+    templates::validate_template(&template_req).map_err(|err| error_with_body(StatusCode::BAD_REQUEST, &err))?;
+
+    let template = FeedbackTemplate {
+        id: Uuid::new_v4().to_string(),
+        name: template_req.name.trim().to_string(),
+        description: template_req.description.filter(|d| !d.trim().is_empty()),
+        sections: template_req
+            .sections
+            .into_iter()
+            .map(|section| TemplateSection {
+                title: section.title.trim().to_string(),
+                questions: section
+                    .questions
+                    .into_iter()
+                    .map(|q| TemplateQuestion {
+                        id: Uuid::new_v4().to_string(),
+                        text: q.text.trim().to_string(),
+                        required: q.required.unwrap_or(true),
+                        kind: q.kind,
+                    })
+                    .collect(),
+            })
+            .collect(),
+        created_by: claims.sub,
+        created_at: chrono::Utc::now(),
+        archived: false,
+    };
+
+    state
+        .feedback_templates
+        .write()
+        .await
+        .insert(template.id.clone(), template.clone());
+
+    Ok(Json(json!(template)))
+}
+
+pub async fn list_feedback_templates(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<FeedbackTemplateQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Archived templates are only listed for managers on request
+    let include_archived = query.include_archived.unwrap_or(false) && auth_user.is_manager();
+
+    let templates = state.feedback_templates.read().await;
+    let mut list: Vec<FeedbackTemplate> = templates
+        .values()
+        .filter(|t| include_archived || !t.archived)
+        .cloned()
+        .collect();
+    list.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(Json(json!(list)))
+}
+
+pub async fn get_feedback_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let templates = state.feedback_templates.read().await;
+    let template = templates.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(json!(template)))
+}
+
+pub async fn archive_feedback_template(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can define feedback templates
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Existing feedback keeps referring to the template, so it is only archived
+    let mut templates = state.feedback_templates.write().await;
+    let template = templates.get_mut(&id).ok_or(StatusCode::NOT_FOUND)?;
+    template.archived = true;
+
+    Ok(Json(json!(template)))
+}
+
+pub async fn create_review_cycle(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(cycle_req): Json<CreateReviewCycleRequest>,
+) -> Result<Json<Value>, ErrorWithBody> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub.clone(),
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can run review cycles
+    if !auth_user.is_manager() {
+        return Err(error_with_body(StatusCode::FORBIDDEN, "only managers can run review cycles"));
+    }
+
+    let now = chrono::Utc::now();
+    if cycle_req.name.trim().is_empty() {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "name is required"));
+    }
+    if cycle_req.deadline <= now {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "deadline must be in the future"));
+    }
+
+    // Reviewers answer the template's questions as they are now, so later
+    // template changes do not affect responses already collected
+    let templates = state.feedback_templates.read().await;
+    let template = templates
+        .get(&cycle_req.template_id)
+        .filter(|t| !t.archived)
+        .ok_or_else(|| error_with_body(StatusCode::BAD_REQUEST, "template_id does not match an active template"))?;
+    let sections = template.sections.clone();
+    drop(templates);
+
+    let cycle = ReviewCycle {
+        id: Uuid::new_v4().to_string(),
+        name: cycle_req.name.trim().to_string(),
+        created_by: claims.sub,
+        deadline: cycle_req.deadline,
+        template_id: cycle_req.template_id,
+        sections,
+        assignments: Vec::new(),
+        status: ReviewCycleStatus::Open,
+        created_at: now,
+        closed_at: None,
+        released_at: None,
+    };
+
+    state.review_cycles.write().await.insert(cycle.id.clone(), cycle.clone());
+
+    Ok(Json(json!(cycle)))
+}
+
+/// Copy of a cycle for responses. Managers see every assignment; everyone
+/// else only sees the ones they have to fill in, so reviewers stay
+/// unknown to the people they review. Answers are only shown to the
+/// reviewer who gave them; everyone else reads them through the results.
+fn review_cycle_view(cycle: &ReviewCycle, viewer: &AuthenticatedUser, now: chrono::DateTime<chrono::Utc>) -> ReviewCycle {
+    let mut view = cycle.clone();
+    view.status = cycle.effective_status(now);
+    if !viewer.is_manager() {
+        view.assignments.retain(|a| a.reviewer_id == viewer.id);
+    }
+    for assignment in view.assignments.iter_mut().filter(|a| a.reviewer_id != viewer.id) {
+        assignment.answers.clear();
+    }
+    view
+}
+
+pub async fn list_review_cycles(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    let now = chrono::Utc::now();
+    let cycles = state.review_cycles.read().await;
+    // Non-managers see the cycles they review in or are reviewed in
+    let mut list: Vec<ReviewCycle> = cycles
+        .values()
+        .filter(|c| {
+            auth_user.is_manager()
+                || c.assignments
+                    .iter()
+                    .any(|a| a.reviewer_id == auth_user.id || a.employee_id == auth_user.id)
+        })
+        .map(|c| review_cycle_view(c, &auth_user, now))
+        .collect();
+    list.sort_by_key(|c| std::cmp::Reverse(c.created_at));
+
+    Ok(Json(json!(list)))
+}
+
+pub async fn get_review_cycle(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    let cycles = state.review_cycles.read().await;
+    let cycle = cycles.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    let involved = cycle
+        .assignments
+        .iter()
+        .any(|a| a.reviewer_id == auth_user.id || a.employee_id == auth_user.id);
+    if !auth_user.is_manager() && !involved {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(Json(json!(review_cycle_view(cycle, &auth_user, chrono::Utc::now()))))
+}
+
+pub async fn assign_reviewers(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(assign_req): Json<AssignReviewersRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can run review cycles
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Employee and reviewers must be existing, active users, and nobody
+    // reviews themselves
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
+    let is_active = |id: &str| by_id.get(id).is_some_and(|u| u.status == UserStatus::Active);
+    if !is_active(&assign_req.employee_id)
+        || assign_req.reviewer_ids.is_empty()
+        || assign_req
+            .reviewer_ids
+            .iter()
+            .any(|r| r == &assign_req.employee_id || !is_active(r))
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    drop(users);
+
+    let mut cycles = state.review_cycles.write().await;
+    let cycle = cycles.get_mut(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    // Assignments can only change while responses are being collected
+    if cycle.effective_status(chrono::Utc::now()) != ReviewCycleStatus::Open {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    for reviewer_id in assign_req.reviewer_ids {
+        let already_assigned = cycle
+            .assignments
+            .iter()
+            .any(|a| a.employee_id == assign_req.employee_id && a.reviewer_id == reviewer_id);
+        if !already_assigned {
+            cycle.assignments.push(ReviewAssignment {
+                id: Uuid::new_v4().to_string(),
+                employee_id: assign_req.employee_id.clone(),
+                reviewer_id,
+                answers: Vec::new(),
+                submitted_at: None,
+            });
+        }
+    }
+
+    Ok(Json(json!(review_cycle_view(cycle, &auth_user, chrono::Utc::now()))))
+}
+
+pub async fn submit_review(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, assignment_id)): Path<(String, String)>,
+    Json(submit_req): Json<SubmitReviewRequest>,
+) -> Result<Json<Value>, ErrorWithBody> {
+    let not_found = || error_with_body(StatusCode::NOT_FOUND, "review assignment not found");
+    let now = chrono::Utc::now();
+    let mut cycles = state.review_cycles.write().await;
+    let cycle = cycles.get_mut(&id).ok_or_else(not_found)?;
+
+    let assignment = cycle
+        .assignments
+        .iter()
+        .find(|a| a.id == assignment_id)
+        .ok_or_else(not_found)?;
+
+    // Only the assigned reviewer can respond
+    if assignment.reviewer_id != claims.sub {
+        return Err(error_with_body(StatusCode::FORBIDDEN, "only the assigned reviewer can respond"));
+    }
+
+    // Responses are locked once the cycle closes
+    if cycle.effective_status(now) != ReviewCycleStatus::Open {
+        return Err(error_with_body(StatusCode::CONFLICT, "responses are locked"));
+    }
+
+    templates::validate_answers(cycle.questions(), &submit_req.answers)
+        .map_err(|err| error_with_body(StatusCode::BAD_REQUEST, &err))?;
+
+    // Resubmitting before the deadline replaces the earlier response
+    let assignment = cycle
+        .assignments
+        .iter_mut()
+        .find(|a| a.id == assignment_id)
+        .ok_or_else(not_found)?;
+    assignment.answers = submit_req.answers;
+    assignment.submitted_at = Some(now);
+
+    Ok(Json(json!(assignment)))
+}
+
+pub async fn close_review_cycle(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can run review cycles
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let mut cycles = state.review_cycles.write().await;
+    let cycle = cycles.get_mut(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    if cycle.status != ReviewCycleStatus::Open {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // A cycle past its deadline was effectively closed at the deadline
+    let now = chrono::Utc::now();
+    cycle.status = ReviewCycleStatus::Closed;
+    cycle.closed_at = Some(now.min(cycle.deadline));
+
+    Ok(Json(json!(review_cycle_view(cycle, &auth_user, now))))
+}
+
+pub async fn release_review_cycle(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can run review cycles
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let mut cycles = state.review_cycles.write().await;
+    let cycle = cycles.get_mut(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    // Results can only be released once responses are locked
+    let now = chrono::Utc::now();
+    if cycle.effective_status(now) != ReviewCycleStatus::Closed {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    cycle.closed_at.get_or_insert(cycle.deadline);
+    cycle.status = ReviewCycleStatus::Released;
+    cycle.released_at = Some(now);
+
+    Ok(Json(json!(review_cycle_view(cycle, &auth_user, now))))
+}
+
+pub async fn get_review_results(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((id, employee_id)): Path<(String, String)>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    let cycles = state.review_cycles.read().await;
+    let cycle = cycles.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+
+    if !cycle.assignments.iter().any(|a| a.employee_id == employee_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    // Managers can follow results at any time; employees only see their
+    // own, once released
+    let own_released = employee_id == auth_user.id && cycle.status == ReviewCycleStatus::Released;
+    if !auth_user.is_manager() && !own_released {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(Json(json!(review::aggregate_results(
+        cycle,
+        &employee_id,
+        state.config.review_min_responses,
+    ))))
+}
+
+/// Local dates of an absence and the working days it is charged.
+struct ChargedDays {
+    time_zone: chrono_tz::Tz,
+    first: chrono::NaiveDate,
+    last: chrono::NaiveDate,
+    working_days: f64,
+}
+
+/// Dates the range in the user's time zone and counts working days from
+/// their schedule and their location's holiday calendar. None if the user
+/// does not exist.
+async fn charged_days(
+    state: &AppState,
+    user_id: &str,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+    start_half_day: bool,
+    end_half_day: bool,
+) -> Option<ChargedDays> {
+    let users = state.users.read().await;
+    let user = users.values().find(|u| u.id == user_id)?.clone();
+    drop(users);
+
+    let time_zone = absence::time_zone_of(&user, state.config.default_time_zone);
+    let first = absence::local_date(start, time_zone);
+    let last = absence::local_date(end, time_zone);
+
+    let calendars = state.holiday_calendars.read().await;
+    let holidays = user
+        .location
+        .as_ref()
+        .and_then(|location| calendars.get(location))
+        .map(|calendar| calendar.holidays.as_slice())
+        .unwrap_or_default();
+
+    Some(ChargedDays {
+        time_zone,
+        first,
+        last,
+        working_days: absence::working_days(first, last, start_half_day, end_half_day, &user.work_schedule, holidays),
+    })
+}
+
+pub async fn get_user_working_days(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<String>,
+    Query(query): Query<WorkingDaysQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Employees can check their own absences, managers anyone's
+    if auth_user.id != user_id && !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if query.end_date < query.start_date {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let span = charged_days(
+        &state,
+        &user_id,
+        query.start_date,
+        query.end_date,
+        query.start_half_day,
+        query.end_half_day,
+    )
+    .await
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(json!({
+        "first_date": span.first,
+        "last_date": span.last,
+        "calendar_days": absence::calendar_days(span.first, span.last),
+        "working_days": span.working_days,
+    })))
+}
+
+pub async fn list_holiday_calendars(State(state): State<AppState>) -> Json<Value> {
+    let calendars = state.holiday_calendars.read().await;
+    let mut list: Vec<&HolidayCalendar> = calendars.values().collect();
+    list.sort_by(|a, b| a.location.cmp(&b.location));
+    Json(json!(list))
+}
+
+pub async fn get_holiday_calendar(
+    State(state): State<AppState>,
+    Path(location): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let calendars = state.holiday_calendars.read().await;
+    let calendar = calendars.get(&location).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(json!(calendar)))
+}
+
+pub async fn import_holiday_calendar(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(location): Path<String>,
+    body: String,
+) -> Result<Json<Value>, ErrorWithBody> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can maintain holiday calendars
+    if !auth_user.is_manager() {
+        return Err(error_with_body(StatusCode::FORBIDDEN, "only managers can import holiday calendars"));
+    }
+
+    let location = location.trim().to_string();
+    if location.is_empty() {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "location is required"));
+    }
+
+    // Importing replaces the location's calendar; absences already filed
+    // keep the working days they were charged
+    let (name, holidays) = holidays::parse_ical(&body)
+        .map_err(|message| error_with_body(StatusCode::BAD_REQUEST, &message))?;
+    let calendar = HolidayCalendar {
+        location: location.clone(),
+        name,
+        holidays,
+        imported_at: chrono::Utc::now(),
+    };
+
+    state
+        .holiday_calendars
+        .write()
+        .await
+        .insert(location, calendar.clone());
+
+    Ok(Json(json!(calendar)))
+}
+
+pub async fn delete_holiday_calendar(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(location): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can maintain holiday calendars
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    state
+        .holiday_calendars
+        .write()
+        .await
+        .remove(&location)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(json!({ "message": "Holiday calendar deleted successfully" })))
+}
+
+/// Error response with a JSON body explaining why the request was refused.
+type ErrorWithBody = (StatusCode, Json<Value>);
+
+fn error_with_body(status: StatusCode, message: &str) -> ErrorWithBody {
+    (status, Json(json!({ "error": message })))
+}
+
+pub async fn create_absence_request(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(absence_req): Json<CreateAbsenceRequest>,
+) -> Result<Json<Value>, ErrorWithBody> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub.clone(),
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only employees can request absences
+    if !auth_user.is_employee() {
+        return Err(error_with_body(StatusCode::FORBIDDEN, "only employees can request absences"));
+    }
+
+    if absence_req.end_date < absence_req.start_date {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "end_date is before start_date"));
+    }
+
+    // Requests may only be filed within the configured window around today
+    let now = chrono::Utc::now();
+    let earliest = now - chrono::Duration::days(state.config.absence_max_past_days);
+    let latest = now + chrono::Duration::days(state.config.absence_max_future_days);
+    if absence_req.start_date < earliest {
+        return Err(error_with_body(
+            StatusCode::BAD_REQUEST,
+            &format!("start_date is more than {} days in the past", state.config.absence_max_past_days),
+        ));
+    }
+    if absence_req.end_date > latest {
+        return Err(error_with_body(
+            StatusCode::BAD_REQUEST,
+            &format!("end_date is more than {} days ahead", state.config.absence_max_future_days),
+        ));
+    }
+
+    let span = charged_days(
+        &state,
+        &claims.sub,
+        absence_req.start_date,
+        absence_req.end_date,
+        absence_req.start_half_day,
+        absence_req.end_half_day,
+    )
+    .await
+    .ok_or_else(|| error_with_body(StatusCode::NOT_FOUND, "user not found"))?;
+    absence::check_half_days(span.first, span.last, absence_req.start_half_day, absence_req.end_half_day)
+        .map_err(|message| error_with_body(StatusCode::BAD_REQUEST, &message))?;
+    if span.working_days <= 0.0 {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "the request covers no working days"));
+    }
+
+    let policy = absence::policy(absence_req.absence_type);
+    let days = absence::calendar_days(span.first, span.last);
+    absence::check_policy(&policy, days, absence_req.documentation.as_deref())
+        .map_err(|message| error_with_body(StatusCode::BAD_REQUEST, &message))?;
+
+    // Approvers are resolved when the request is filed
+    let approvals = if policy.requires_approval {
+        let rules = state.approval_rules.read().await;
+        let chain = approval::chain_for(&rules, policy.absence_type, span.working_days);
+        drop(rules);
+        let users = state.users.read().await;
+        let by_id = org::index_by_id(&users);
+        let requester = by_id
+            .get(claims.sub.as_str())
+            .ok_or_else(|| error_with_body(StatusCode::NOT_FOUND, "user not found"))?;
+        approval::build_steps(chain, &by_id, requester)
+    } else {
+        Vec::new()
+    };
+
+    let mut absences = state.absences.write().await;
+
+    // Pending and approved requests of the same employee may not overlap
+    if let Some(conflict) = absences.iter().find(|a| {
+        a.user_id == claims.sub
+            && a.status.is_active()
+            && absence::overlaps(
+                a,
+                span.time_zone,
+                span.first,
+                span.last,
+                absence_req.start_half_day,
+                absence_req.end_half_day,
+            )
+    }) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": "overlaps an existing absence request",
+                "conflicting_request": conflict,
+            })),
+        ));
+    }
+
+    // Balance-based types may not ask for more than is left after other
+    // pending requests
+    let requested_days = span.working_days;
+    let mut leave = state.leave.write().await;
+    if policy.uses_balance {
+        leave.sync(&state.config, &claims.sub, policy.absence_type, now.date_naive());
+        let available = leave.balance(&claims.sub, policy.absence_type)
+            - pending_days(&absences, &claims.sub, policy.absence_type);
+        if requested_days > available {
+            return Err(error_with_body(
+                StatusCode::BAD_REQUEST,
+                &format!("insufficient balance: {} days available", leave::round_days(available)),
+            ));
+        }
+    }
+
+    let user_id = claims.sub.clone();
+    let mut absence = AbsenceRequest {
+        id: Uuid::new_v4().to_string(),
+        user_id,
+        absence_type: absence_req.absence_type,
+        start_date: absence_req.start_date,
+        end_date: absence_req.end_date,
+        start_half_day: absence_req.start_half_day,
+        end_half_day: absence_req.end_half_day,
+        working_days: span.working_days,
+        reason: absence_req.reason,
+        documentation: absence_req.documentation.filter(|d| !d.trim().is_empty()),
+        status: AbsenceStatus::Pending,
+        created_at: now,
+        decided_by: None,
+        decided_at: None,
+        decision_comment: None,
+        history: Vec::new(),
+        approvals,
+    };
+    absence.set_status(AbsenceStatus::Pending, &claims.sub, None);
+
+    // Types that need no approval are approved right away
+    if !policy.requires_approval {
+        let comment = "No approval required for this absence type".to_string();
+        absence.set_status(AbsenceStatus::Approved, SYSTEM_ACTOR, Some(comment.clone()));
+        absence.decided_by = Some(SYSTEM_ACTOR.to_string());
+        absence.decided_at = Some(now);
+        absence.decision_comment = Some(comment);
+    }
+
+    if policy.uses_balance && absence.status == AbsenceStatus::Approved {
+        leave.deduct(&absence.user_id, absence.absence_type, requested_days, &absence.id, span.first);
+    }
+    absences.push(absence.clone());
+
+    Ok(Json(json!(absence)))
+}
+
+/// Days in `user_id`'s requests of this type that await a decision.
+fn pending_days(absences: &[AbsenceRequest], user_id: &str, absence_type: AbsenceType) -> f64 {
+    absences
+        .iter()
+        .filter(|a| a.user_id == user_id && a.absence_type == absence_type && a.status == AbsenceStatus::Pending)
+        .map(absence::requested_days)
+        .sum()
+}
+
+pub async fn get_leave_balances(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Employees see their own balances, managers everyone's
+    if auth_user.id != user_id && !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let users = state.users.read().await;
+    if !users.values().any(|u| u.id == user_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    drop(users);
+
+    let today = chrono::Utc::now().date_naive();
+    let absences = state.absences.read().await;
+    let mut leave = state.leave.write().await;
+
+    let balances: Vec<LeaveBalance> = AbsenceType::ALL
+        .into_iter()
+        .filter(|t| absence::policy(*t).uses_balance)
+        .map(|absence_type| {
+            leave.sync(&state.config, &user_id, absence_type, today);
+            let balance = leave.balance(&user_id, absence_type);
+            let pending = leave::round_days(pending_days(&absences, &user_id, absence_type));
+            let (carried_over, carry_over_expires_on) = leave.carry_over(&user_id, absence_type);
+            LeaveBalance {
+                absence_type,
+                annual_entitlement: state.config.leave_entitlements.get(&absence_type).copied().unwrap_or(0.0),
+                balance,
+                pending,
+                available: leave::round_days(balance - pending),
+                carried_over,
+                carry_over_expires_on,
+            }
+        })
+        .collect();
+
+    Ok(Json(json!(balances)))
+}
+
+pub async fn get_leave_ledger(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<String>,
+    Query(query): Query<LeaveLedgerQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Employees see their own ledger, managers everyone's
+    if auth_user.id != user_id && !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let users = state.users.read().await;
+    if !users.values().any(|u| u.id == user_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    drop(users);
+
+    let today = chrono::Utc::now().date_naive();
+    let mut leave = state.leave.write().await;
+    for absence_type in AbsenceType::ALL.into_iter().filter(|t| absence::policy(*t).uses_balance) {
+        leave.sync(&state.config, &user_id, absence_type, today);
+    }
+
+    let transactions: Vec<&LeaveTransaction> = leave
+        .transactions(&user_id)
+        .into_iter()
+        .filter(|t| query.absence_type.is_none_or(|kind| t.absence_type == kind))
+        .collect();
+
+    Ok(Json(json!(transactions)))
+}
+
+pub async fn create_leave_adjustment(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<String>,
+    Json(adjustment_req): Json<LeaveAdjustmentRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub.clone(),
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can correct balances
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    if !absence::policy(adjustment_req.absence_type).uses_balance
+        || adjustment_req.amount == 0.0
+        || !adjustment_req.amount.is_finite()
+        || adjustment_req.note.trim().is_empty()
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let users = state.users.read().await;
+    if !users.values().any(|u| u.id == user_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    drop(users);
+
+    let today = chrono::Utc::now().date_naive();
+    let mut leave = state.leave.write().await;
+    leave.sync(&state.config, &user_id, adjustment_req.absence_type, today);
+    let transaction = leave.post(
+        &user_id,
+        adjustment_req.absence_type,
+        LeaveTransactionKind::Adjustment,
+        adjustment_req.amount,
+        today,
+        None,
+        Some(format!("{} (by {})", adjustment_req.note.trim(), claims.sub)),
+    );
+
+    Ok(Json(json!(transaction)))
+}
+
+pub async fn list_absence_types() -> Json<Value> {
+    let policies: Vec<absence::AbsencePolicy> = AbsenceType::ALL.into_iter().map(absence::policy).collect();
+    Json(json!(policies))
+}
+
+pub async fn get_my_absences(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<AbsenceListQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = claims.sub.clone();
+    let auth_user = AuthenticatedUser {
+        id: user_id.clone(),
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only employees can view their own absences
+    if !auth_user.is_employee() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let users = state.users.read().await;
+    let time_zones = absence_time_zones(&users, state.config.default_time_zone);
+    let absences = state.absences.read().await;
+    let my_absences: Vec<AbsenceRequest> = absences
+        .iter()
+        .filter(|a| a.user_id == user_id)
+        .cloned()
+        .collect();
+
+    let page = page_absences(my_absences, &query, &time_zones, state.config.default_time_zone)?;
+    Ok(Json(json!(page)))
+}
+
+pub async fn list_all_absences(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<AbsenceListQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can view all absences
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let users = state.users.read().await;
+    let time_zones = absence_time_zones(&users, state.config.default_time_zone);
+    let absences = state.absences.read().await;
+
+    let page = page_absences(absences.clone(), &query, &time_zones, state.config.default_time_zone)?;
+    Ok(Json(json!(page)))
+}
+
+/// Each user's time zone, keyed by user ID, for dating their absences.
+fn absence_time_zones(
+    users: &std::collections::HashMap<String, User>,
+    default_time_zone: chrono_tz::Tz,
+) -> std::collections::HashMap<String, chrono_tz::Tz> {
+    users
+        .values()
+        .map(|u| (u.id.clone(), absence::time_zone_of(u, default_time_zone)))
+        .collect()
+}
+
+/// Applies the list filters, sorts by start date, latest first, and
+/// paginates. Date filters compare the requester's local dates.
+fn page_absences(
+    mut list: Vec<AbsenceRequest>,
+    query: &AbsenceListQuery,
+    time_zones: &std::collections::HashMap<String, chrono_tz::Tz>,
+    default_time_zone: chrono_tz::Tz,
+) -> Result<Paginated<AbsenceRequest>, StatusCode> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if to < from {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    list.retain(|a| {
+        let time_zone = time_zones.get(&a.user_id).copied().unwrap_or(default_time_zone);
+        let (first, last) = absence::local_dates(a, time_zone);
+        query.status.as_ref().is_none_or(|status| a.status == *status)
+            && query.absence_type.is_none_or(|absence_type| a.absence_type == absence_type)
+            && query.user_id.as_ref().is_none_or(|user_id| a.user_id == *user_id)
+            && query.from.is_none_or(|from| last >= from)
+            && query.to.is_none_or(|to| first <= to)
+    });
+    list.sort_by(|a, b| b.start_date.cmp(&a.start_date).then_with(|| b.created_at.cmp(&a.created_at)));
+    Ok(Paginated::from_vec(list, query.page, query.per_page))
+}
+
+/// Longest pay period an export covers.
+const MAX_PAY_PERIOD_DAYS: i64 = 366;
+
+pub async fn export_absences(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<AbsenceExportQuery>,
+) -> Result<axum::response::Response, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can export absences for payroll
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    if query.to < query.from || absence::calendar_days(query.from, query.to) > MAX_PAY_PERIOD_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Former employees are included; their absences still need paying out
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
+    let calendars = state.holiday_calendars.read().await;
+    let absences = state.absences.read().await;
+
+    let mut entries: Vec<PayrollEntry> = absences
+        .iter()
+        .filter_map(|a| {
+            let user = by_id.get(a.user_id.as_str())?;
+            let holidays = user
+                .location
+                .as_ref()
+                .and_then(|location| calendars.get(location))
+                .map(|calendar| calendar.holidays.as_slice())
+                .unwrap_or_default();
+            let time_zone = absence::time_zone_of(user, state.config.default_time_zone);
+            payroll::entry(a, user, time_zone, holidays, query.from, query.to)
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| a.user_id.cmp(&b.user_id))
+            .then_with(|| a.first_day.cmp(&b.first_day))
+    });
+
+    let totals = payroll::totals(&entries);
+    if query.format == ExportFormat::Json {
+        return Ok(Json(json!({
+            "from": query.from,
+            "to": query.to,
+            "absences": entries,
+            "totals": totals,
+        }))
+        .into_response());
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in payroll::csv_rows(&entries, &totals) {
+        writer
+            .serialize(row)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    let body = writer
+        .into_inner()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let disposition = format!("attachment; filename=\"absences-{}-{}.csv\"", query.from, query.to);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Longest window the team calendar returns at once.
+const MAX_CALENDAR_DAYS: i64 = 92;
+
+pub async fn get_absence_calendar(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<AbsenceCalendarQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    if query.to < query.from || absence::calendar_days(query.from, query.to) > MAX_CALENDAR_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
+
+    // Managers see any team, by default their own reports; others only
+    // the team they belong to
+    let own_manager = by_id.get(auth_user.id.as_str()).and_then(|u| u.manager_id.as_deref());
+    let team: Vec<&User> = match query.manager_id.as_deref() {
+        Some(manager_id) if auth_user.is_manager() || own_manager == Some(manager_id) => {
+            org::direct_reports(&by_id, manager_id)
+                .into_iter()
+                .filter(|u| u.status == UserStatus::Active)
+                .collect()
+        }
+        Some(_) => return Err(StatusCode::FORBIDDEN),
+        None if auth_user.is_manager() => org::direct_reports(&by_id, &auth_user.id)
+            .into_iter()
+            .filter(|u| u.status == UserStatus::Active)
+            .collect(),
+        None => org::team_of(&by_id, &auth_user.id),
+    };
+
+    let absences = state.absences.read().await;
+    let default_time_zone = state.config.default_time_zone;
+    let days: Vec<CalendarDay> = query
+        .from
+        .iter_days()
+        .take_while(|date| *date <= query.to)
+        .map(|date| {
+            let mut entries = Vec::new();
+            let mut absent = 0;
+            for member in &team {
+                let time_zone = absence::time_zone_of(member, default_time_zone);
+                let mut member_absent = false;
+                for a in absences.iter().filter(|a| {
+                    a.user_id == member.id
+                        && (coverage::counts_as_absent(&a.status) || a.status == AbsenceStatus::Pending)
+                        && coverage::covers(a, time_zone, date)
+                }) {
+                    let (first, last) = absence::local_dates(a, time_zone);
+                    member_absent |= coverage::counts_as_absent(&a.status);
+                    entries.push(CalendarEntry {
+                        absence_id: a.id.clone(),
+                        user_id: member.id.clone(),
+                        // The kind of leave is private to the person and managers
+                        absence_type: (auth_user.is_manager() || member.id == auth_user.id).then_some(a.absence_type),
+                        status: a.status.clone(),
+                        half_day: (a.start_half_day && date == first) || (a.end_half_day && date == last),
+                    });
+                }
+                if member_absent {
+                    absent += 1;
+                }
+            }
+
+            let present = team.len() - absent;
+            CalendarDay {
+                date,
+                absences: entries,
+                present,
+                below_minimum: present < state.config.team_min_coverage,
+            }
+        })
+        .collect();
+
+    let members: Vec<UserInfo> = team.iter().map(|u| UserInfo::from(*u)).collect();
+
+    Ok(Json(json!({
+        "team": members,
+        "min_coverage": state.config.team_min_coverage,
+        "days": days,
+    })))
+}
+
+pub async fn get_calendar_feed(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Value>, StatusCode> {
+    let feeds = state.calendar_feeds.read().await;
+    let feed = feeds
+        .values()
+        .find(|f| f.user_id == claims.sub)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(json!(feed)))
+}
+
+pub async fn create_calendar_feed(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(feed_req): Json<CreateCalendarFeedRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can subscribe to the whole organization's absences
+    if feed_req.scope == CalendarFeedScope::Org && !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let token = tokens::new_secret_token();
+    let feed = CalendarFeed {
+        user_id: auth_user.id.clone(),
+        token_hash: tokens::hash_token(&token),
+        scope: feed_req.scope,
+        created_at: chrono::Utc::now(),
+    };
+
+    // A new token replaces the old one, so the previous URL stops working
+    let mut feeds = state.calendar_feeds.write().await;
+    feeds.retain(|_, f| f.user_id != feed.user_id);
+    feeds.insert(feed.token_hash.clone(), feed.clone());
+
+    // The store only keeps the hash, so the URL is shown this once
+    let mut response = json!(feed);
+    response["url"] = json!(format!("/api/calendar-feed/{}.ics", token));
+    Ok(Json(response))
+}
+
+pub async fn revoke_calendar_feed(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Value>, StatusCode> {
+    let mut feeds = state.calendar_feeds.write().await;
+    let count = feeds.len();
+    feeds.retain(|_, f| f.user_id != claims.sub);
+    if feeds.len() == count {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(Json(json!({ "message": "Calendar feed revoked successfully" })))
+}
+
+/// Serves a calendar feed to clients that authenticate with the token in
+/// the URL alone.
+pub async fn get_calendar_feed_ics(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let token = token.strip_suffix(".ics").unwrap_or(&token);
+    let token_hash = tokens::hash_token(token);
+    let feeds = state.calendar_feeds.read().await;
+    let feed = feeds
+        .get(&token_hash)
+        .filter(|_| !token.is_empty())
+        .cloned()
+        .ok_or(StatusCode::NOT_FOUND)?;
+    drop(feeds);
+
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
+    // Feeds stop working when their owner is deactivated
+    let owner = by_id
+        .get(feed.user_id.as_str())
+        .copied()
+        .filter(|u| u.status == UserStatus::Active)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let is_manager = owner.role == "manager";
+
+    // Permissions are checked on every fetch, as the owner's role may have
+    // changed since the feed was created
+    let members: Vec<&User> = match feed.scope {
+        CalendarFeedScope::Own => vec![owner],
+        CalendarFeedScope::Team if is_manager => {
+            let mut members: Vec<&User> = org::direct_reports(&by_id, &owner.id)
+                .into_iter()
+                .filter(|u| u.status == UserStatus::Active)
+                .collect();
+            members.push(owner);
+            members
+        }
+        CalendarFeedScope::Team => org::team_of(&by_id, &owner.id),
+        CalendarFeedScope::Org if is_manager => by_id
+            .values()
+            .copied()
+            .filter(|u| u.status == UserStatus::Active)
+            .collect(),
+        CalendarFeedScope::Org => return Err(StatusCode::FORBIDDEN),
+    };
+
+    let absences = state.absences.read().await;
+    let mut events = Vec::new();
+    for member in &members {
+        let time_zone = absence::time_zone_of(member, state.config.default_time_zone);
+        // The kind of leave is private to the person and managers
+        let show_type = is_manager || member.id == owner.id;
+        for a in absences
+            .iter()
+            .filter(|a| a.user_id == member.id && calendar_feed::is_listed(&a.status))
+        {
+            let (first, last) = absence::local_dates(a, time_zone);
+            let what = if show_type { calendar_feed::type_label(a.absence_type) } else { "Out of office" };
+            events.push(calendar_feed::FeedEvent {
+                absence: a,
+                first,
+                last,
+                summary: format!("{} - {}", member.name, what),
+            });
+        }
+    }
+    events.sort_by(|a, b| a.first.cmp(&b.first).then_with(|| a.absence.id.cmp(&b.absence.id)));
+
+    let name = match feed.scope {
+        CalendarFeedScope::Own => format!("{} - Absences", owner.name),
+        CalendarFeedScope::Team => "Team absences".to_string(),
+        CalendarFeedScope::Org => "NEWWORK absences".to_string(),
+    };
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        calendar_feed::render(&name, &events),
+    ))
+}
+
+pub async fn list_my_approvals(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    let today = chrono::Utc::now().date_naive();
+    let delegations = approval::delegations_held_by(&state.delegations.read().await, &auth_user.id, today);
+    let absences = state.absences.read().await;
+
+    // Pending requests whose current step the user can decide, directly
+    // or as a delegate
+    let awaiting: Vec<&AbsenceRequest> = absences
+        .iter()
+        .filter(|a| a.status == AbsenceStatus::Pending)
+        .filter(|a| {
+            approval::current_step(a).is_some_and(|index| {
+                approval::authority(&a.approvals[index], a, &auth_user.id, auth_user.is_manager(), &delegations, today)
+                    .is_some()
+            })
+        })
+        .collect();
+
+    Ok(Json(json!(awaiting)))
+}
+
+pub async fn get_approval_rules(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can configure approval chains
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let rules = state.approval_rules.read().await;
+    Ok(Json(json!(*rules)))
+}
+
+pub async fn replace_approval_rules(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(rules): Json<Vec<ApprovalRule>>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can configure approval chains
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Every rule needs at least one step, and named approvers must be
+    // existing, active users
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
+    let valid = rules.iter().all(|rule| {
+        !rule.steps.is_empty()
+            && rule.min_working_days.is_none_or(|days| days >= 0.0)
+            && rule.steps.iter().all(|step| match step {
+                Approver::User { user_id } => by_id
+                    .get(user_id.as_str())
+                    .is_some_and(|u| u.status == UserStatus::Active),
+                Approver::LineManager | Approver::AnyManager => true,
+            })
+    });
+    if !valid {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    drop(users);
+
+    // Requests already filed keep the chain they were given
+    let mut current = state.approval_rules.write().await;
+    *current = rules;
+
+    Ok(Json(json!(*current)))
+}
+
+pub async fn list_delegations(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Managers see every delegation, others those they are part of
+    let delegations = state.delegations.read().await;
+    let list: Vec<&Delegation> = delegations
+        .iter()
+        .filter(|d| auth_user.is_manager() || d.delegator_id == auth_user.id || d.delegate_id == auth_user.id)
+        .collect();
+
+    Ok(Json(json!(list)))
+}
+
+pub async fn create_delegation(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(delegation_req): Json<CreateDelegationRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if delegation_req.delegate_id == claims.sub || delegation_req.end_date < delegation_req.start_date {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Only people who approve requests have approvals to hand over, and
+    // the delegate must be an existing, active user
+    let users = state.users.read().await;
+    let rules = state.approval_rules.read().await;
+    let by_id = org::index_by_id(&users);
+    if !approval::can_approve(&claims.sub, claims.role == "manager", &by_id, &rules) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if !by_id
+        .get(delegation_req.delegate_id.as_str())
+        .is_some_and(|u| u.status == UserStatus::Active)
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    drop(rules);
+    drop(users);
+
+    // Approvals delegated onwards are not passed along again, so each
+    // user hands over to one person per date range, and not to someone
+    // who has handed over their own approvals for part of it
+    let mut delegations = state.delegations.write().await;
+    let overlaps = |d: &Delegation| {
+        d.start_date <= delegation_req.end_date && delegation_req.start_date <= d.end_date
+    };
+    if delegations
+        .iter()
+        .any(|d| overlaps(d) && (d.delegator_id == claims.sub || d.delegator_id == delegation_req.delegate_id))
+    {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let delegation = Delegation {
+        id: Uuid::new_v4().to_string(),
+        delegator_id: claims.sub,
+        delegate_id: delegation_req.delegate_id,
+        start_date: delegation_req.start_date,
+        end_date: delegation_req.end_date,
+        created_at: chrono::Utc::now(),
+    };
+    delegations.push(delegation.clone());
+
+    Ok(Json(json!(delegation)))
+}
+
+pub async fn delete_delegation(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    let mut delegations = state.delegations.write().await;
+    let delegation = delegations
+        .iter()
+        .find(|d| d.id == id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Delegators can end their own delegations, managers any
+    if delegation.delegator_id != auth_user.id && !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    delegations.retain(|d| d.id != id);
+
+    Ok(Json(json!({ "message": "Delegation deleted successfully" })))
+}
+
+pub async fn withdraw_absence_request(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let mut absences = state.absences.write().await;
+    let absence = absences
+        .iter_mut()
+        .find(|a| a.id == id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Employees can only withdraw their own requests
+    if absence.user_id != claims.sub {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Only undecided requests can be withdrawn; approved ones need a
+    // cancellation request
+    if absence.status != AbsenceStatus::Pending {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    absence.set_status(AbsenceStatus::Withdrawn, &claims.sub, None);
+
+    Ok(Json(json!(absence)))
+}
+
+pub async fn request_absence_cancellation(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let mut absences = state.absences.write().await;
+    let absence = absences
+        .iter_mut()
+        .find(|a| a.id == id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Employees can only cancel their own absences
+    if absence.user_id != claims.sub {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Only approved absences that have not ended yet can be cancelled; a
+    // manager confirms or declines the request
+    if absence.status != AbsenceStatus::Approved || absence.end_date < chrono::Utc::now() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    absence.set_status(AbsenceStatus::CancellationRequested, &claims.sub, None);
+
+    Ok(Json(json!(absence)))
+}
+
+pub async fn update_absence_status(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(update_req): Json<UpdateAbsenceStatusRequest>,
+) -> Result<Json<Value>, ErrorWithBody> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    let now = chrono::Utc::now();
+    let today = now.date_naive();
+    let delegations = approval::delegations_held_by(&state.delegations.read().await, &auth_user.id, today);
+
+    // Users are locked before absences, as everywhere else
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
+
+    let mut absences = state.absences.write().await;
+    let index = absences
+        .iter()
+        .position(|a| a.id == id)
+        .ok_or_else(|| error_with_body(StatusCode::NOT_FOUND, "absence request not found"))?;
+    let team = org::team_of(&by_id, &absences[index].user_id);
+    let absence = &mut absences[index];
+
+    // Clone status for comparison
+    let current_status = absence.status.clone();
+
+    // Pending requests are decided step by step by each step's approver
+    // or their delegate; every other change, and requests filed without
+    // an approval chain, are up to managers
+    let (step_index, on_behalf_of) = match approval::current_step(absence) {
+        Some(index) if current_status == AbsenceStatus::Pending => {
+            let on_behalf_of = approval::authority(
+                &absence.approvals[index],
+                absence,
+                &auth_user.id,
+                auth_user.is_manager(),
+                &delegations,
+                today,
+            )
+            .ok_or_else(|| error_with_body(StatusCode::FORBIDDEN, "not an approver of the current step"))?
+            .on_behalf_of();
+            (Some(index), on_behalf_of)
+        }
+        _ if auth_user.is_manager() => (None, None),
+        _ => return Err(error_with_body(StatusCode::FORBIDDEN, "only managers can change this request")),
+    };
+
+    if !current_status.manager_can_move_to(&update_req.status) {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "status change not allowed"));
+    }
+
+    // Rejections must say why
+    let comment = update_req.comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    if update_req.status == AbsenceStatus::Rejected && comment.is_none() {
+        return Err(error_with_body(StatusCode::BAD_REQUEST, "a comment is required when rejecting"));
+    }
+
+    // An approval only settles the request once no steps are left
+    let is_final = match step_index {
+        Some(index) => {
+            update_req.status != AbsenceStatus::Approved
+                || absence.approvals[index + 1..]
+                    .iter()
+                    .all(|s| s.status != ApprovalStepStatus::Waiting)
+        }
+        None => true,
+    };
+    let uses_balance = absence::policy(absence.absence_type).uses_balance;
+
+    // Approving must not leave the team short-handed unless the manager
+    // explicitly accepts it; a team of one has nobody to cover for them
+    if is_final
+        && current_status == AbsenceStatus::Pending
+        && update_req.status == AbsenceStatus::Approved
+        && !update_req.override_coverage.unwrap_or(false)
+        && team.len() > 1
+    {
+        let conflicts = coverage::conflicts(
+            &absences[index],
+            &team,
+            &absences,
+            state.config.default_time_zone,
+            state.config.team_min_coverage,
+        );
+        if !conflicts.is_empty() {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(json!({
+                    "error": "approving would drop team coverage below the minimum; resend with override_coverage to approve anyway",
+                    "coverage_conflicts": conflicts,
+                })),
+            ));
+        }
+    }
+    let absence = &mut absences[index];
+
+    // Approved days of balance-based types are deducted right away
+    if is_final
+        && current_status == AbsenceStatus::Pending
+        && update_req.status == AbsenceStatus::Approved
+        && uses_balance
+    {
+        let days = absence::requested_days(absence);
+        let mut leave = state.leave.write().await;
+        leave.sync(&state.config, &absence.user_id, absence.absence_type, today);
+        if leave.balance(&absence.user_id, absence.absence_type) < days {
+            return Err(error_with_body(StatusCode::CONFLICT, "insufficient balance"));
+        }
+        let time_zone = by_id
+            .get(absence.user_id.as_str())
+            .map(|u| absence::time_zone_of(u, state.config.default_time_zone))
+            .unwrap_or(state.config.default_time_zone);
+        let (first_day, _) = absence::local_dates(absence, time_zone);
+        leave.deduct(&absence.user_id, absence.absence_type, days, &absence.id, first_day);
+    }
+
+    if let Some(index) = step_index {
+        let step = &mut absence.approvals[index];
+        step.status = if update_req.status == AbsenceStatus::Approved {
+            ApprovalStepStatus::Approved
+        } else {
+            ApprovalStepStatus::Rejected
+        };
+        step.decided_by = Some(auth_user.id.clone());
+        step.on_behalf_of = on_behalf_of;
+        step.decided_at = Some(now);
+        step.comment = comment.clone();
+
+        // A rejection ends the chain
+        if update_req.status == AbsenceStatus::Rejected {
+            for later in absence.approvals[index + 1..].iter_mut() {
+                if later.status == ApprovalStepStatus::Waiting {
+                    later.status = ApprovalStepStatus::Skipped;
+                }
+            }
+        }
+    }
+    if !is_final {
+        return Ok(Json(json!(absence)));
+    }
+
+    // Cancelled absences give their days back
+    if update_req.status == AbsenceStatus::Cancelled {
+        state.leave.write().await.restore(&absence.id);
+    }
+
+    // The approve/reject decision is kept apart from later changes
+    if current_status == AbsenceStatus::Pending {
+        absence.decided_by = Some(auth_user.id.clone());
+        absence.decided_at = Some(now);
+        absence.decision_comment = comment.clone();
+    }
+    absence.set_status(update_req.status.clone(), &auth_user.id, comment);
+
+    Ok(Json(json!(absence)))
+}
+
+pub async fn list_users(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Value>, StatusCode> {
+    // Managers and co-workers can list users (co-workers need it for feedback author info)
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    if !auth_user.is_manager() && !auth_user.is_coworker() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let users = state.users.read().await;
+    // Return only user info (not password hashes) - all users (managers, employees, co-workers)
+    let user_info: Vec<UserInfo> = users
+        .values()
+        .map(UserInfo::from)
+        .collect();
+
+    Ok(Json(json!(user_info)))
+}
+
+pub async fn create_user(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(mut create_req): Json<CreateUserRequest>,
+) -> Result<Json<Value>, ErrorWithBody> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can create users
+    if !auth_user.is_manager() {
+        return Err(error_with_body(StatusCode::FORBIDDEN, "only managers can create users"));
+    }
+
+    create_req.email = directory::normalize_email(&create_req.email);
+    directory::validate_create_user(&create_req).map_err(|err| error_with_body(StatusCode::BAD_REQUEST, &err))?;
+
+    // Check if user with this email already exists
+    let users = state.users.read().await;
+    if users.contains_key(&create_req.email) {
+        return Err(error_with_body(StatusCode::CONFLICT, "a user with this email already exists"));
+    }
+    if let Some(manager_id) = &create_req.manager_id {
+        if !users.values().any(|u| &u.id == manager_id) {
+            return Err(error_with_body(StatusCode::BAD_REQUEST, "manager_id does not match an existing user"));
+        }
+    }
+    drop(users);
+
+    let new_user = User {
+        id: Uuid::new_v4().to_string(),
+        name: create_req.name,
+        email: create_req.email,
+        password_hash: String::new(), // Set by the user when accepting the invitation
+        role: create_req.role,
+        manager_id: create_req.manager_id,
+        status: UserStatus::Invited,
+        avatar_version: None,
+        location: None,
+        time_zone: None,
+        work_schedule: WorkSchedule::default(),
+    };
+
+    let mut users = state.users.write().await;
+    if users.contains_key(&new_user.email) {
+        return Err(error_with_body(StatusCode::CONFLICT, "a user with this email already exists"));
+    }
+    users.insert(new_user.email.clone(), new_user.clone());
+    drop(users);
+
+    invite_user(&state, &new_user, &auth_user.id).await;
+
+    Ok(Json(json!(UserInfo::from(&new_user))))
+}
+
+pub async fn import_users(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ImportUsersQuery>,
+    mut multipart: Multipart,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can import users
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Expect the CSV in a multipart field named "file"
+    let mut csv_bytes = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+    {
+        if field.name() == Some("file") {
+            csv_bytes = Some(field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?);
+            break;
+        }
+    }
+    let csv_bytes = csv_bytes.ok_or(StatusCode::BAD_REQUEST)?;
+
+    let users = state.users.read().await;
+    let ImportCheck { valid_rows, errors, total_rows } = directory::check_import(&csv_bytes, &users);
+    drop(users);
+
+    let dry_run = query.dry_run.unwrap_or(false);
+
+    // Imports are all-or-nothing: any invalid row aborts the whole file
+    let imported = !dry_run && errors.is_empty();
+
+    let mut new_users = Vec::with_capacity(valid_rows.len());
+    for create_req in valid_rows {
+        // Imported users are invited, just like in `create_user`
+        new_users.push(User {
+            id: Uuid::new_v4().to_string(),
+            name: create_req.name,
+            email: create_req.email,
+            password_hash: String::new(),
+            role: create_req.role,
+            manager_id: create_req.manager_id,
+            status: UserStatus::Invited,
+            avatar_version: None,
+            location: None,
+            time_zone: None,
+            work_schedule: WorkSchedule::default(),
+        });
+    }
+
+    if imported {
+        let mut users = state.users.write().await;
+        // Re-check under the write lock in case a user was created meanwhile
+        if new_users.iter().any(|u| users.contains_key(&u.email)) {
+            return Err(StatusCode::CONFLICT);
+        }
+        for user in &new_users {
+            users.insert(user.email.clone(), user.clone());
+        }
+        drop(users);
+
+        for user in &new_users {
+            invite_user(&state, user, &auth_user.id).await;
+        }
+    }
+
+    Ok(Json(json!(ImportUsersResponse {
+        dry_run,
+        imported,
+        total_rows,
+        errors,
+        users: new_users.iter().map(UserInfo::from).collect(),
+    })))
+}
+
+pub async fn export_users(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can export the user directory
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let users = state.users.read().await;
+    let mut sorted: Vec<&User> = users.values().collect();
+    sorted.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.email.cmp(&b.email)));
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for user in sorted {
+        writer
+            .serialize(directory::ExportRow::from(user))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    let body = writer
+        .into_inner()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"users.csv\""),
+        ],
+        body,
+    ))
+}
+
+/// Creates an invitation for a pending user and mails the one-time link.
+/// Delivery failures are logged; the manager can resend later.
+async fn invite_user(state: &AppState, user: &User, invited_by: &str) -> Invitation {
+    let now = chrono::Utc::now();
+    let invitation = Invitation {
+        id: Uuid::new_v4().to_string(),
+        user_id: user.id.clone(),
+        email: user.email.clone(),
+        token_hash: String::new(),
+        invited_by: invited_by.to_string(),
+        status: InvitationStatus::Pending,
+        created_at: now,
+        expires_at: now + chrono::Duration::hours(state.config.invite_ttl_hours),
+        sent_count: 1,
+    };
+
+    let token = tokens::new_secret_token();
+    let invitation = Invitation { token_hash: tokens::hash_token(&token), ..invitation };
+
+    let mut invitations = state.invitations.write().await;
+    invitations.insert(invitation.token_hash.clone(), invitation.clone());
+    drop(invitations);
+
+    send_invitation_mail(state, &user.name, &invitation, &token).await;
+    invitation
+}
+
+/// The token is only known while sending; the store keeps its hash.
+async fn send_invitation_mail(state: &AppState, name: &str, invitation: &Invitation, token: &str) {
+    let message = MailMessage {
+        to: invitation.email.clone(),
+        subject: "You have been invited to NEWWORK".to_string(),
+        body: format!(
+            "Hi {},\n\nYou have been invited to join NEWWORK. Set your password here:\n\n{}/accept-invite?token={}\n\nThis link expires on {}.",
+            name,
+            state.config.app_base_url.trim_end_matches('/'),
+            token,
+            invitation.expires_at.format("%Y-%m-%d %H:%M UTC"),
+        ),
+    };
+
+    if let Err(err) = state.mailer.send(&message).await {
+        tracing::warn!("Failed to send invitation {}: {}", invitation.id, err);
+    }
+}
+
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can manage invitations
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let now = chrono::Utc::now();
+    let invitations = state.invitations.read().await;
+    let mut list: Vec<Invitation> = invitations
+        .values()
+        .map(|invitation| Invitation {
+            status: invitation.effective_status(now),
+            ..invitation.clone()
+        })
+        .collect();
+    list.sort_by_key(|i| std::cmp::Reverse(i.created_at));
+
+    Ok(Json(json!(list)))
+}
+
+pub async fn resend_invitation(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can manage invitations
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let users = state.users.read().await;
+    let mut invitations = state.invitations.write().await;
+    let invitation = invitations
+        .values()
+        .find(|i| i.id == id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Accepted and revoked invitations are final; expired ones can be renewed
+    if invitation.status != InvitationStatus::Pending {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let name = users
+        .values()
+        .find(|u| u.id == invitation.user_id)
+        .map(|u| u.name.clone())
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Rotate the token so earlier links stop working
+    let now = chrono::Utc::now();
+    let token = tokens::new_secret_token();
+    let old_hash = invitation.token_hash.clone();
+    let mut invitation = invitations.remove(&old_hash).ok_or(StatusCode::NOT_FOUND)?;
+    invitation.token_hash = tokens::hash_token(&token);
+    invitation.expires_at = now + chrono::Duration::hours(state.config.invite_ttl_hours);
+    invitation.sent_count += 1;
+    invitations.insert(invitation.token_hash.clone(), invitation.clone());
+    drop(invitations);
+    drop(users);
+
+    send_invitation_mail(&state, &name, &invitation, &token).await;
+
+    Ok(Json(json!(invitation)))
+}
+
+pub async fn revoke_invitation(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, StatusCode> {
+    let auth_user = AuthenticatedUser {
+        id: claims.sub,
+        email: claims.email.clone(),
+        role: claims.role.clone(),
+    };
+
+    // Only managers can manage invitations
+    if !auth_user.is_manager() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let mut users = state.users.write().await;
+    let mut invitations = state.invitations.write().await;
+    let invitation = invitations
+        .values_mut()
+        .find(|i| i.id == id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if invitation.status != InvitationStatus::Pending {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    invitation.status = InvitationStatus::Revoked;
+
+    // The pending account was never activated, so remove it with the invitation
+    users.retain(|_, u| !(u.id == invitation.user_id && u.status == UserStatus::Invited));
+
+    Ok(Json(json!(invitation)))
+}
+
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    Json(accept_req): Json<AcceptInvitationRequest>,
+) -> Result<Json<LoginResponse>, StatusCode> {
+    if accept_req.token.is_empty() || accept_req.password.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Hash before locking; bcrypt is deliberately slow
+    let password_hash = bcrypt::hash(&accept_req.password, 12)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let now = chrono::Utc::now();
+    let mut users = state.users.write().await;
+    let mut invitations = state.invitations.write().await;
+    let invitation = invitations
+        .get_mut(&tokens::hash_token(&accept_req.token))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if invitation.effective_status(now) != InvitationStatus::Pending {
+        return Err(StatusCode::GONE);
+    }
+
+    let user = users
+        .values_mut()
+        .find(|u| u.id == invitation.user_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    user.password_hash = password_hash;
+    user.status = UserStatus::Active;
 
-    let result: Value = response.json().await?;
-    if let Some(array) = result.as_array() {
-        if let Some(first) = array.first() {
-            if let Some(generated_text) = first.get("generated_text") {
-                if let Some(text) = generated_text.as_str() {
-                    return Ok(text.to_string());
-                }
-            }
-        }
-    }
-    
-    // Fallback if parsing fails
-    Ok(format!("[AI-Polished] {}", content.trim()))
+    // Tokens are single use
+    invitation.status = InvitationStatus::Accepted;
+
+    let token = generate_token(&user.id, &user.email, &user.role)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(LoginResponse {
+        token,
+        user: UserInfo::from(&*user),
+    }))
 }
 
-pub async fn create_absence_request(
+pub async fn get_org_tree(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
-    Json(absence_req): Json<CreateAbsenceRequest>,
 ) -> Result<Json<Value>, StatusCode> {
     let auth_user = AuthenticatedUser {
-        id: claims.sub.clone(),
+        id: claims.sub,
         email: claims.email.clone(),
         role: claims.role.clone(),
     };
 
-    // Only employees can request absences
-    if !auth_user.is_employee() {
-        return Err(StatusCode::FORBIDDEN);
-    }
-
-    let user_id = claims.sub.clone();
-    let absence = AbsenceRequest {
-        id: Uuid::new_v4().to_string(),
-        user_id,
-        start_date: absence_req.start_date,
-        end_date: absence_req.end_date,
-        reason: absence_req.reason,
-        status: AbsenceStatus::Pending,
-        created_at: chrono::Utc::now(),
-    };
-
-    let mut absences = state.absences.write().await;
-    absences.push(absence.clone());
+    // Any authenticated user can see the org chart; only managers see full profiles
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
 
-    Ok(Json(json!(absence)))
+    Ok(Json(json!(org::org_tree(&by_id, auth_user.is_manager()))))
 }
 
-pub async fn get_my_absences(
+pub async fn get_user_reports(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
+    Path(user_id): Path<String>,
 ) -> Result<Json<Value>, StatusCode> {
-    let user_id = claims.sub.clone();
     let auth_user = AuthenticatedUser {
-        id: user_id.clone(),
+        id: claims.sub,
         email: claims.email.clone(),
         role: claims.role.clone(),
     };
 
-    // Only employees can view their own absences
-    if !auth_user.is_employee() {
-        return Err(StatusCode::FORBIDDEN);
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
+    if !by_id.contains_key(user_id.as_str()) {
+        return Err(StatusCode::NOT_FOUND);
     }
 
-    let absences = state.absences.read().await;
-    let my_absences: Vec<&AbsenceRequest> = absences
-        .iter()
-        .filter(|a| a.user_id == user_id)
+    let reports: Vec<org::OrgEntry> = org::direct_reports(&by_id, &user_id)
+        .into_iter()
+        .map(|u| org::entry(u, auth_user.is_manager()))
         .collect();
 
-    Ok(Json(json!(my_absences)))
+    Ok(Json(json!(reports)))
 }
 
-pub async fn list_all_absences(
+pub async fn get_user_chain(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
+    Path(user_id): Path<String>,
 ) -> Result<Json<Value>, StatusCode> {
     let auth_user = AuthenticatedUser {
         id: claims.sub,
@@ -298,21 +2802,26 @@ pub async fn list_all_absences(
         role: claims.role.clone(),
     };
 
-    // Only managers can view all absences
-    if !auth_user.is_manager() {
-        return Err(StatusCode::FORBIDDEN);
+    let users = state.users.read().await;
+    let by_id = org::index_by_id(&users);
+    if !by_id.contains_key(user_id.as_str()) {
+        return Err(StatusCode::NOT_FOUND);
     }
 
-    let absences = state.absences.read().await;
-    Ok(Json(json!(absences.clone())))
-}
+    // Nearest manager first, ending at the top of the hierarchy
+    let chain: Vec<org::OrgEntry> = org::management_chain(&by_id, &user_id)
+        .into_iter()
+        .map(|u| org::entry(u, auth_user.is_manager()))
+        .collect();
 
+    Ok(Json(json!(chain)))
+}
 
-pub async fn update_absence_status(
+pub async fn upload_avatar(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
-    Path(id): Path<String>,
-    Json(update_req): Json<UpdateAbsenceStatusRequest>,
+    Path(user_id): Path<String>,
+    mut multipart: Multipart,
 ) -> Result<Json<Value>, StatusCode> {
     let auth_user = AuthenticatedUser {
         id: claims.sub,
@@ -320,64 +2829,121 @@ pub async fn update_absence_status(
         role: claims.role.clone(),
     };
 
-    // Only managers can approve/reject absences
-    if !auth_user.is_manager() {
+    // Users manage their own avatar; managers can manage anyone's
+    if !auth_user.is_manager() && auth_user.id != user_id {
         return Err(StatusCode::FORBIDDEN);
     }
 
-    let mut absences = state.absences.write().await;
-    let absence = absences
-        .iter_mut()
-        .find(|a| a.id == id)
-        .ok_or(StatusCode::NOT_FOUND)?;
-    
-    // Clone status for comparison
-    let current_status = absence.status.clone();
+    let users = state.users.read().await;
+    if !users.values().any(|u| u.id == user_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    drop(users);
 
-    // Can only update if status is pending
-    if current_status != AbsenceStatus::Pending {
-        return Err(StatusCode::BAD_REQUEST);
+    // Expect the image in a multipart field named "avatar"
+    let max_bytes = state.config.avatar_max_bytes;
+    let mut upload = None;
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+    {
+        if field.name() != Some("avatar") {
+            continue;
+        }
+        if !field.content_type().is_some_and(|ct| ct.starts_with("image/")) {
+            return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        }
+
+        // Read in chunks so oversized uploads are rejected without buffering them
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(|_| StatusCode::BAD_REQUEST)? {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(StatusCode::PAYLOAD_TOO_LARGE);
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        upload = Some(bytes);
+        break;
+    }
+    let upload = upload.ok_or(StatusCode::BAD_REQUEST)?;
+
+    // Decoding and resizing are CPU-bound, keep them off the async workers
+    let thumbnails = tokio::task::spawn_blocking(move || render_thumbnails(&upload))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|err| match err {
+            AvatarError::UnsupportedType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AvatarError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            AvatarError::InvalidImage(_) => StatusCode::BAD_REQUEST,
+        })?;
+
+    for (size, png) in thumbnails {
+        state
+            .storage
+            .put(&avatar_key(&user_id, size), png)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     }
 
-    absence.status = update_req.status.clone();
+    let mut users = state.users.write().await;
+    let user = users
+        .values_mut()
+        .find(|u| u.id == user_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    user.avatar_version = Some(Uuid::new_v4().simple().to_string());
 
-    Ok(Json(json!(absence)))
+    Ok(Json(json!(UserInfo::from(&*user))))
 }
 
-pub async fn list_users(
+pub async fn get_avatar(
     State(state): State<AppState>,
-    Extension(claims): Extension<Claims>,
-) -> Result<Json<Value>, StatusCode> {
-    // Managers and co-workers can list users (co-workers need it for feedback author info)
-    let auth_user = AuthenticatedUser {
-        id: claims.sub,
-        email: claims.email.clone(),
-        role: claims.role.clone(),
-    };
-
-    if !auth_user.is_manager() && !auth_user.is_coworker() {
-        return Err(StatusCode::FORBIDDEN);
+    Path(user_id): Path<String>,
+    Query(query): Query<AvatarQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let size = query.size.unwrap_or(DEFAULT_AVATAR_SIZE);
+    if !AVATAR_SIZES.contains(&size) {
+        return Err(StatusCode::BAD_REQUEST);
     }
 
+    // Served without a JWT so avatar_url works in image tags. The version
+    // is random and only handed to signed-in users, so it stands in for
+    // their token; old versions stop working once the avatar changes.
     let users = state.users.read().await;
-    // Return only user info (not password hashes) - all users (managers, employees, co-workers)
-    let user_info: Vec<UserInfo> = users
+    let is_current = users
         .values()
-        .map(|u| UserInfo {
-            id: u.id.clone(),
-            name: u.name.clone(),
-            email: u.email.clone(),
-            role: u.role.clone(),
-        })
-        .collect();
+        .find(|u| u.id == user_id)
+        .ok_or(StatusCode::NOT_FOUND)?
+        .avatar_version
+        .as_deref()
+        .is_some_and(|version| query.v.as_deref() == Some(version));
+    drop(users);
 
-    Ok(Json(json!(user_info)))
+    if !is_current {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let png = state
+        .storage
+        .get(&avatar_key(&user_id, size))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            // URLs carry the avatar version, so responses can be cached
+            (header::CACHE_CONTROL, "private, max-age=86400"),
+        ],
+        png,
+    ))
 }
 
-pub async fn create_user(
+pub async fn delete_avatar(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
-    Json(create_req): Json<CreateUserRequest>,
+    Path(user_id): Path<String>,
 ) -> Result<Json<Value>, StatusCode> {
     let auth_user = AuthenticatedUser {
         id: claims.sub,
@@ -385,47 +2951,29 @@ pub async fn create_user(
         role: claims.role.clone(),
     };
 
-    // Only managers can create users
-    if !auth_user.is_manager() {
+    // Users manage their own avatar; managers can manage anyone's
+    if !auth_user.is_manager() && auth_user.id != user_id {
         return Err(StatusCode::FORBIDDEN);
     }
 
-    // Validate role
-    if !["manager", "employee", "coworker"].contains(&create_req.role.as_str()) {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
-    // Check if user with this email already exists
-    let users = state.users.read().await;
-    if users.contains_key(&create_req.email) {
-        return Err(StatusCode::CONFLICT);
-    }
+    let mut users = state.users.write().await;
+    let user = users
+        .values_mut()
+        .find(|u| u.id == user_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    user.avatar_version = None;
+    let user_info = UserInfo::from(&*user);
     drop(users);
 
-    // Hash password
-    let password_hash = bcrypt::hash(&create_req.password, 12)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // Generate user ID
-    let user_id = uuid::Uuid::new_v4().to_string();
-
-    let new_user = User {
-        id: user_id.clone(),
-        name: create_req.name.clone(),
-        email: create_req.email.clone(),
-        password_hash,
-        role: create_req.role.clone(),
-    };
-
-    let mut users = state.users.write().await;
-    users.insert(create_req.email.clone(), new_user.clone());
+    for size in AVATAR_SIZES {
+        state
+            .storage
+            .delete(&avatar_key(&user_id, size))
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
 
-    Ok(Json(json!(UserInfo {
-        id: user_id,
-        name: create_req.name,
-        email: create_req.email,
-        role: create_req.role,
-    })))
+    Ok(Json(json!(user_info)))
 }
 
 pub async fn delete_user(
@@ -458,7 +3006,21 @@ pub async fn delete_user(
         .map(|(email, _)| email.clone())
         .ok_or(StatusCode::NOT_FOUND)?;
 
-    users.remove(&email_to_remove);
+    let removed = users.remove(&email_to_remove).ok_or(StatusCode::NOT_FOUND)?;
+
+    // Direct reports move up to the removed user's manager
+    for user in users.values_mut() {
+        if user.manager_id.as_deref() == Some(user_id.as_str()) {
+            user.manager_id = removed.manager_id.clone();
+        }
+    }
+    drop(users);
+
+    // Requests waiting on the removed user can still be decided
+    approval::release_steps_of(&mut state.absences.write().await, &user_id);
+
+    let mut invitations = state.invitations.write().await;
+    invitations.retain(|_, invitation| invitation.user_id != user_id);
 
     Ok(Json(json!({ "message": "User deleted successfully" })))
 }
@@ -475,52 +3037,250 @@ pub async fn add_data_item_feedback(
         role: claims.role.clone(),
     };
 
-    // Only co-workers can add feedback to data items
-    if !auth_user.is_coworker() {
-        return Err(StatusCode::FORBIDDEN);
+    // Feedback and replies alike need some text
+    if feedback_req.content.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
     }
 
+    let data_items = state.data_items.read().await;
+    let item = data_items.get(&item_id).ok_or(StatusCode::NOT_FOUND)?;
+    check_can_post(&state, &auth_user, item, feedback_req.parent_id.as_deref())?;
+    drop(data_items);
+
+    // Redeemed without holding the data items, as it locks the previews
+    let suggestion = redeem_preview(&state, &claims.sub, feedback_req.preview_id, &feedback_req.content).await?;
+    let (polish_status, polished_content, published_version) =
+        resolve_polish_choice(feedback_req.polish, suggestion, feedback_req.publish)?;
+
+    // The item may have changed while the preview was redeemed
     let mut data_items = state.data_items.write().await;
     let item = data_items
         .get_mut(&item_id)
         .ok_or(StatusCode::NOT_FOUND)?;
-
-    // Co-workers can only comment on non-deleted items
-    if item.is_deleted {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
-    let mut content = feedback_req.content.clone();
-    
-    // Polish content using AI if requested
-    if feedback_req.polish.unwrap_or(false) {
-        content = polish_feedback_with_ai(&content).await
-            .unwrap_or_else(|_| feedback_req.content.clone());
-    }
+    check_can_post(&state, &auth_user, item, feedback_req.parent_id.as_deref())?;
 
     let feedback = DataItemFeedback {
         id: uuid::Uuid::new_v4().to_string(),
+        parent_id: feedback_req.parent_id,
         from_user_id: claims.sub.clone(),
         content: feedback_req.content,
-        polished_content: if feedback_req.polish.unwrap_or(false) {
-            Some(content)
-        } else {
-            None
-        },
+        polished_content,
+        polish_status,
+        polish_error: None,
+        published_version,
         created_at: chrono::Utc::now(),
+        updated_at: None,
+        revisions: vec![],
+        retracted_at: None,
+        moderation: None,
     };
 
     item.feedbacks.push(feedback.clone());
     item.updated_at = chrono::Utc::now();
 
+    if polish_status == PolishStatus::Pending {
+        state.polish_queue.enqueue(PolishJob {
+            target: PolishTarget::DataItemFeedback {
+                item_id: item_id.clone(),
+                feedback_id: feedback.id.clone(),
+            },
+            content: feedback.content.clone(),
+        });
+    }
+
+    Ok(Json(json!(feedback)))
+}
+
+/// Whether `auth_user` may add feedback to `item`, or reply to `parent_id`
+/// on it.
+fn check_can_post(
+    state: &AppState,
+    auth_user: &AuthenticatedUser,
+    item: &DataItem,
+    parent_id: Option<&str>,
+) -> Result<(), StatusCode> {
+    // Only co-workers can add feedback to data items; co-workers, the item
+    // owner and managers can reply to it
+    let can_post = match parent_id {
+        None => auth_user.is_coworker(),
+        Some(_) => auth_user.is_coworker() || auth_user.is_manager() || item.owner_id == auth_user.id,
+    };
+    if !can_post {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Co-workers can only comment on non-deleted items
+    if item.is_deleted {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if let Some(parent_id) = parent_id {
+        let parent = item
+            .feedbacks
+            .iter()
+            .find(|f| f.id == parent_id)
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        if !parent.is_visible() {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        if reply_depth(&item.feedbacks, parent_id) + 1 > state.config.feedback_reply_max_depth {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    Ok(())
+}
+
+pub async fn update_data_item_feedback(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((item_id, feedback_id)): Path<(String, String)>,
+    Json(update_req): Json<UpdateFeedbackRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if update_req.content.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut data_items = state.data_items.write().await;
+    let item = data_items
+        .get_mut(&item_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let feedback = item
+        .feedbacks
+        .iter_mut()
+        .find(|f| f.id == feedback_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let is_author = feedback.from_user_id == claims.sub;
+    edit_feedback(feedback, is_author, update_req.content, state.config.feedback_edit_window_minutes)?;
+    let feedback = feedback.clone();
+    item.updated_at = chrono::Utc::now();
+
+    Ok(Json(json!(feedback)))
+}
+
+pub async fn retract_data_item_feedback(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((item_id, feedback_id)): Path<(String, String)>,
+) -> Result<Json<Value>, StatusCode> {
+    let mut data_items = state.data_items.write().await;
+    let item = data_items
+        .get_mut(&item_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let feedback = item
+        .feedbacks
+        .iter_mut()
+        .find(|f| f.id == feedback_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let is_author = feedback.from_user_id == claims.sub;
+    retract(feedback, is_author)?;
+
     Ok(Json(json!(feedback)))
 }
 
+pub async fn hide_data_item_feedback(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((item_id, feedback_id)): Path<(String, String)>,
+    Json(hide_req): Json<HideFeedbackRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let moderation = moderation_from(&claims, hide_req)?;
+
+    let mut data_items = state.data_items.write().await;
+    let item = data_items
+        .get_mut(&item_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let feedback = item
+        .feedbacks
+        .iter_mut()
+        .find(|f| f.id == feedback_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    hide(feedback, moderation)?;
+    let mut view = feedback.clone();
+    if view.from_user_id != claims.sub {
+        view.publish_only();
+    }
+
+    Ok(Json(json!(view)))
+}
+
+/// Number of ancestors above `feedback_id` (0 for top-level feedback).
+fn reply_depth(feedbacks: &[DataItemFeedback], feedback_id: &str) -> usize {
+    let mut depth = 0;
+    let mut current = feedbacks.iter().find(|f| f.id == feedback_id);
+    while let Some(parent_id) = current.and_then(|f| f.parent_id.as_deref()) {
+        depth += 1;
+        // Bounded by the number of entries in case of corrupted links
+        if depth > feedbacks.len() {
+            break;
+        }
+        current = feedbacks.iter().find(|f| f.id == parent_id);
+    }
+    depth
+}
+
+/// Arranges feedback into reply threads, oldest first. Unless
+/// `include_hidden` is set, retracted and hidden entries are left out, or
+/// kept as empty placeholders when they have replies still shown.
+fn feedback_tree(mut feedbacks: Vec<DataItemFeedback>, include_hidden: bool) -> Vec<DataItemFeedbackNode> {
+    feedbacks.sort_by_key(|f| f.created_at);
+    let mut replies_of: std::collections::HashMap<Option<String>, Vec<DataItemFeedback>> =
+        std::collections::HashMap::new();
+    for feedback in feedbacks {
+        replies_of.entry(feedback.parent_id.clone()).or_default().push(feedback);
+    }
+
+    fn thread(
+        parent_id: Option<String>,
+        replies_of: &mut std::collections::HashMap<Option<String>, Vec<DataItemFeedback>>,
+        include_hidden: bool,
+    ) -> Vec<DataItemFeedbackNode> {
+        let mut nodes = Vec::new();
+        for feedback in replies_of.remove(&parent_id).unwrap_or_default() {
+            let replies = thread(Some(feedback.id.clone()), replies_of, include_hidden);
+            if include_hidden || feedback.is_visible() {
+                nodes.push(DataItemFeedbackNode { feedback, removed: false, replies });
+            } else if !replies.is_empty() {
+                let placeholder = DataItemFeedback {
+                    from_user_id: String::new(),
+                    content: String::new(),
+                    polished_content: None,
+                    polish_error: None,
+                    revisions: vec![],
+                    retracted_at: None,
+                    moderation: None,
+                    ..feedback
+                };
+                nodes.push(DataItemFeedbackNode { feedback: placeholder, removed: true, replies });
+            }
+        }
+        nodes
+    }
+
+    thread(None, &mut replies_of, include_hidden)
+}
+
+/// A data item as `reader_id` sees it, with feedback as reply threads and
+/// only the published text of feedback they did not write.
+fn data_item_view(item: &DataItem, reader_id: &str, include_hidden: bool) -> Value {
+    let mut view = item.clone();
+    for feedback in view.feedbacks.iter_mut().filter(|f| f.from_user_id != reader_id) {
+        feedback.publish_only();
+    }
+    let threads = feedback_tree(std::mem::take(&mut view.feedbacks), include_hidden);
+
+    let mut body = json!(view);
+    body["feedbacks"] = json!(threads);
+    body
+}
+
 // Data Items handlers with access control
 
 pub async fn list_data_items(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
+    Query(query): Query<DataItemQuery>,
 ) -> Result<Json<Value>, StatusCode> {
     let data_items = state.data_items.read().await;
     let auth_user = AuthenticatedUser {
@@ -542,6 +3302,12 @@ pub async fn list_data_items(
         return Err(StatusCode::FORBIDDEN);
     };
 
+    let include_hidden = include_hidden(query.include_hidden, &auth_user.role);
+    let items: Vec<Value> = items
+        .into_iter()
+        .map(|item| data_item_view(item, &auth_user.id, include_hidden))
+        .collect();
+
     Ok(Json(json!(items)))
 }
 
@@ -549,6 +3315,7 @@ pub async fn get_data_item(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
     Path(item_id): Path<String>,
+    Query(query): Query<DataItemQuery>,
 ) -> Result<Json<Value>, StatusCode> {
     let data_items = state.data_items.read().await;
     let item = data_items
@@ -574,7 +3341,9 @@ pub async fn get_data_item(
         return Err(StatusCode::FORBIDDEN);
     }
 
-    Ok(Json(json!(item)))
+    let include_hidden = include_hidden(query.include_hidden, &auth_user.role);
+
+    Ok(Json(data_item_view(item, &auth_user.id, include_hidden)))
 }
 
 pub async fn create_data_item(
@@ -648,10 +3417,8 @@ pub async fn update_data_item(
         true // Manager can update all
     } else if auth_user.is_employee() {
         item.owner_id == auth_user.id // Employee can only update own (by user ID)
-    } else if auth_user.is_coworker() {
-        false // Co-worker cannot update (read-only)
     } else {
-        false
+        false // Co-worker cannot update (read-only)
     };
 
     if !can_write {
@@ -670,7 +3437,7 @@ pub async fn update_data_item(
     }
     item.updated_at = chrono::Utc::now();
 
-    Ok(Json(json!(item)))
+    Ok(Json(data_item_view(item, &auth_user.id, false)))
 }
 
 pub async fn delete_data_item(
@@ -694,10 +3461,8 @@ pub async fn delete_data_item(
         true // Manager can delete all
     } else if auth_user.is_employee() {
         item.owner_id == auth_user.id // Employee can only delete own (by user ID)
-    } else if auth_user.is_coworker() {
-        false // Co-worker cannot delete (read-only)
     } else {
-        false
+        false // Co-worker cannot delete (read-only)
     };
 
     if !can_delete {
@@ -0,0 +1,207 @@
+use chrono::{Days, NaiveDate};
+
+use crate::models::Holiday;
+
+/// Longest event expanded into individual holidays, to keep a malformed
+/// file from producing years of days off.
+const MAX_EVENT_DAYS: u64 = 31;
+
+/// Extracts holidays from an iCalendar (RFC 5545) file. Every `VEVENT`
+/// becomes one holiday per day it covers; `DTEND` is exclusive and
+/// defaults to the day after `DTSTART`. Recurrence rules are not expanded,
+/// so files must list each year's dates. Returns the calendar name from
+/// `X-WR-CALNAME`, if present.
+pub fn parse_ical(text: &str) -> Result<(Option<String>, Vec<Holiday>), String> {
+    let mut calendar_name = None;
+    let mut holidays = Vec::new();
+    let mut event: Option<Event> = None;
+    // Components nested in an event, such as alarms, have their own
+    // properties that must not overwrite the event's
+    let mut nested = 0;
+
+    for line in unfold(text) {
+        let Some((head, value)) = line.split_once(':') else { continue };
+        let name = head.split(';').next().unwrap_or_default().to_ascii_uppercase();
+
+        if event.is_some() && (nested > 0 || name == "BEGIN") && !value.eq_ignore_ascii_case("VEVENT") {
+            match name.as_str() {
+                "BEGIN" => nested += 1,
+                "END" => nested -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (name.as_str(), event.as_mut()) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some(Event::default());
+                nested = 0;
+            }
+            ("END", Some(current)) if value.eq_ignore_ascii_case("VEVENT") => {
+                holidays.extend(current.holidays()?);
+                event = None;
+                nested = 0;
+            }
+            ("DTSTART", Some(current)) => current.start = Some(parse_date_value(value)?),
+            ("DTEND", Some(current)) => current.end = Some(parse_date_value(value)?),
+            ("SUMMARY", Some(current)) => current.summary = Some(unescape(value)),
+            ("X-WR-CALNAME", None) => calendar_name = Some(unescape(value)),
+            _ => {}
+        }
+    }
+
+    if holidays.is_empty() {
+        return Err("no events found".to_string());
+    }
+    holidays.sort_by_key(|h| h.date);
+    holidays.dedup_by_key(|h| h.date);
+    Ok((calendar_name, holidays))
+}
+
+#[derive(Default)]
+struct Event {
+    start: Option<DateValue>,
+    end: Option<DateValue>,
+    summary: Option<String>,
+}
+
+/// A `DATE` or `DATE-TIME` value; only the calendar date matters, plus
+/// whether a date-time end falls exactly on midnight.
+#[derive(Clone, Copy)]
+struct DateValue {
+    date: NaiveDate,
+    at_midnight: bool,
+}
+
+impl Event {
+    fn holidays(&self) -> Result<Vec<Holiday>, String> {
+        let start = self.start.ok_or("event without DTSTART")?;
+        // Exclusive end: date-only and midnight ends stop before their day
+        let end = match self.end {
+            Some(end) if end.at_midnight => end.date,
+            Some(end) => end.date + Days::new(1),
+            None => start.date + Days::new(1),
+        };
+        let end = end.max(start.date + Days::new(1));
+        if end > start.date + Days::new(MAX_EVENT_DAYS) {
+            return Err(format!("event starting {} is longer than {} days", start.date, MAX_EVENT_DAYS));
+        }
+
+        let name = self.summary.clone().unwrap_or_else(|| "Holiday".to_string());
+        Ok(start
+            .date
+            .iter_days()
+            .take_while(|d| *d < end)
+            .map(|date| Holiday {
+                date,
+                name: name.clone(),
+            })
+            .collect())
+    }
+}
+
+fn parse_date_value(value: &str) -> Result<DateValue, String> {
+    let value = value.trim();
+    let date = value
+        .get(..8)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok())
+        .ok_or_else(|| format!("invalid date '{}'", value))?;
+    // "20261225" or "20261225T000000[Z]"
+    let at_midnight = value.len() == 8 || value.get(9..15) == Some("000000");
+    Ok(DateValue { date, at_midnight })
+}
+
+/// Joins folded lines: a line starting with a space or tab continues the
+/// previous one.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        let raw = raw.trim_end_matches('\r');
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push(' '),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates(holidays: &[Holiday]) -> Vec<String> {
+        holidays.iter().map(|h| h.date.to_string()).collect()
+    }
+
+    fn calendar(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nX-WR-CALNAME:Public\r\n  holidays\r\n{}END:VCALENDAR\r\n", events)
+    }
+
+    #[test]
+    fn unfolds_continued_lines() {
+        let ics = calendar("BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20261225\r\nSUMMARY:Christmas\r\n\t Day\r\nEND:VEVENT\r\n");
+        let (name, holidays) = parse_ical(&ics).unwrap();
+        assert_eq!(name.as_deref(), Some("Public holidays"));
+        assert_eq!(holidays[0].name, "Christmas Day");
+        assert_eq!(dates(&holidays), ["2026-12-25"]);
+    }
+
+    #[test]
+    fn treats_date_ends_as_exclusive_and_date_time_ends_by_their_day() {
+        let ics = calendar(concat!(
+            "BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20260101\r\nDTEND;VALUE=DATE:20260102\r\nEND:VEVENT\r\n",
+            // Ends at midnight, so the 7th is not included
+            "BEGIN:VEVENT\r\nDTSTART:20260106T000000Z\r\nDTEND:20260107T000000Z\r\nEND:VEVENT\r\n",
+            // Ends during the day, so that day is included
+            "BEGIN:VEVENT\r\nDTSTART:20260301T090000\r\nDTEND:20260302T120000\r\nEND:VEVENT\r\n",
+        ));
+        let (_, holidays) = parse_ical(&ics).unwrap();
+        assert_eq!(dates(&holidays), ["2026-01-01", "2026-01-06", "2026-03-01", "2026-03-02"]);
+    }
+
+    #[test]
+    fn expands_multi_day_events_within_a_limit() {
+        let ics = calendar("BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20261224\r\nDTEND;VALUE=DATE:20261227\r\nSUMMARY:Christmas\r\nEND:VEVENT\r\n");
+        let (_, holidays) = parse_ical(&ics).unwrap();
+        assert_eq!(dates(&holidays), ["2026-12-24", "2026-12-25", "2026-12-26"]);
+        assert!(holidays.iter().all(|h| h.name == "Christmas"));
+
+        let too_long = calendar("BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20260101\r\nDTEND;VALUE=DATE:20260301\r\nEND:VEVENT\r\n");
+        assert!(parse_ical(&too_long).is_err());
+    }
+
+    #[test]
+    fn ignores_time_zone_and_alarm_blocks() {
+        let ics = calendar(concat!(
+            "BEGIN:VTIMEZONE\r\nTZID:Europe/Berlin\r\nBEGIN:STANDARD\r\nDTSTART:19701025T030000\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n",
+            "BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20261003\r\nSUMMARY:Unity Day\r\n",
+            "BEGIN:VALARM\r\nACTION:DISPLAY\r\nSUMMARY:Reminder\r\nTRIGGER:-PT15M\r\nEND:VALARM\r\n",
+            "END:VEVENT\r\n",
+        ));
+        let (_, holidays) = parse_ical(&ics).unwrap();
+        assert_eq!(dates(&holidays), ["2026-10-03"]);
+        assert_eq!(holidays[0].name, "Unity Day");
+    }
+
+    #[test]
+    fn rejects_files_without_events() {
+        assert!(parse_ical(&calendar("")).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::models::PolishStatus;
+use crate::polish::{polish_checked, PolishError};
+use crate::state::AppState;
+
+/// Feedback entry a polishing job writes its result to.
+#[derive(Debug, Clone)]
+pub enum PolishTarget {
+    Feedback { feedback_id: String },
+    DataItemFeedback { item_id: String, feedback_id: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct PolishJob {
+    pub target: PolishTarget,
+    /// Text to polish; results are discarded if the entry was edited since
+    pub content: String,
+}
+
+pub type PolishJobReceiver = mpsc::UnboundedReceiver<PolishJob>;
+
+/// Handle for enqueueing polishing work, held in `AppState`.
+#[derive(Clone)]
+pub struct PolishQueue {
+    sender: mpsc::UnboundedSender<PolishJob>,
+}
+
+impl PolishQueue {
+    pub fn new() -> (Self, PolishJobReceiver) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+
+    pub fn enqueue(&self, job: PolishJob) {
+        if self.sender.send(job).is_err() {
+            tracing::error!("Polish worker is not running, job dropped");
+        }
+    }
+}
+
+/// Runs polishing jobs in the background, at most `polish_workers` at a time.
+pub fn spawn_polish_worker(state: AppState, mut receiver: PolishJobReceiver) {
+    let permits = Arc::new(Semaphore::new(state.config.polish_workers.max(1)));
+
+    tokio::spawn(async move {
+        while let Some(job) = receiver.recv().await {
+            let permit = permits
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            let state = state.clone();
+
+            tokio::spawn(async move {
+                let result = polish_with_retries(&state, &job.content).await;
+                record_result(&state, &job, result).await;
+                drop(permit);
+            });
+        }
+    });
+}
+
+/// Calls the polisher with a per-attempt timeout, retrying transient
+/// failures with exponential backoff.
+async fn polish_with_retries(state: &AppState, content: &str) -> Result<String, PolishError> {
+    let config = &state.config;
+    let timeout = Duration::from_secs(config.polish_timeout_secs);
+    let mut attempt = 1;
+
+    loop {
+        let result = tokio::time::timeout(timeout, polish_checked(state.polisher.as_ref(), content))
+            .await
+            .unwrap_or(Err(PolishError::Timeout));
+
+        match result {
+            Err(err) if err.is_retryable() && attempt < config.polish_max_attempts => {
+                let backoff = config.polish_backoff_ms.saturating_mul(2u64.saturating_pow(attempt - 1));
+                tracing::warn!(
+                    "Polish attempt {} failed ({}), retrying in {}ms",
+                    attempt,
+                    err,
+                    backoff
+                );
+                tokio::time::sleep(Duration::from_millis(backoff)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn record_result(state: &AppState, job: &PolishJob, result: Result<String, PolishError>) {
+    if let Err(err) = &result {
+        tracing::warn!("{} polisher failed: {}", state.polisher.name(), err);
+    }
+    let (status, polished_content, error) = match result {
+        Ok(polished) => (PolishStatus::Succeeded, Some(polished), None),
+        Err(err) => (PolishStatus::Failed, None, Some(err.to_string())),
+    };
+
+    match &job.target {
+        PolishTarget::Feedback { feedback_id } => {
+            let mut feedbacks = state.feedbacks.write().await;
+            let Some(feedback) = feedbacks
+                .iter_mut()
+                .find(|f| &f.id == feedback_id && f.content == job.content)
+            else {
+                return;
+            };
+            feedback.polished_content = polished_content;
+            feedback.polish_status = status;
+            feedback.polish_error = error;
+        }
+        PolishTarget::DataItemFeedback { item_id, feedback_id } => {
+            let mut data_items = state.data_items.write().await;
+            let Some(feedback) = data_items
+                .get_mut(item_id)
+                .and_then(|item| item.feedbacks.iter_mut().find(|f| &f.id == feedback_id))
+                .filter(|f| f.content == job.content)
+            else {
+                return;
+            };
+            feedback.polished_content = polished_content;
+            feedback.polish_status = status;
+            feedback.polish_error = error;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use tokio::time::Instant;
+
+    use super::*;
+    use crate::config::Config;
+    use crate::polish::FeedbackPolisher;
+    use crate::test_support::feedback;
+
+    /// Returns the scripted results in order, then never answers.
+    struct ScriptedPolisher {
+        results: Mutex<VecDeque<Result<String, PolishError>>>,
+    }
+
+    impl ScriptedPolisher {
+        fn unused(&self) -> usize {
+            self.results.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait]
+    impl FeedbackPolisher for ScriptedPolisher {
+        fn name(&self) -> &'static str {
+            "scripted"
+        }
+
+        async fn polish(&self, _content: &str) -> Result<String, PolishError> {
+            let next = self.results.lock().unwrap().pop_front();
+            match next {
+                Some(result) => result,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    /// Three attempts of at most 5 seconds, backing off 100ms and then 200ms.
+    fn state_with(results: Vec<Result<String, PolishError>>) -> (AppState, Arc<ScriptedPolisher>) {
+        let (mut state, _jobs) = AppState::new();
+        let polisher = Arc::new(ScriptedPolisher {
+            results: Mutex::new(results.into()),
+        });
+        state.polisher = polisher.clone();
+        state.config = Arc::new(Config {
+            polish_timeout_secs: 5,
+            polish_max_attempts: 3,
+            polish_backoff_ms: 100,
+            ..Config::from_env()
+        });
+        (state, polisher)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_transient_failures_with_backoff() {
+        let (state, polisher) = state_with(vec![
+            Err(PolishError::Status(503)),
+            Err(PolishError::Status(429)),
+            Ok(" Polished. ".to_string()),
+        ]);
+        let started = Instant::now();
+
+        let result = polish_with_retries(&state, "text").await;
+        assert_eq!(result.unwrap(), "Polished.");
+        assert_eq!(started.elapsed(), Duration::from_millis(300));
+        assert_eq!(polisher.unused(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_the_last_attempt() {
+        let (state, polisher) = state_with(vec![
+            Err(PolishError::Status(503)),
+            Err(PolishError::Status(503)),
+            Err(PolishError::Status(503)),
+            Ok("Polished.".to_string()),
+        ]);
+
+        let result = polish_with_retries(&state, "text").await;
+        assert!(matches!(result, Err(PolishError::Status(503))));
+        assert_eq!(polisher.unused(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn times_out_hanging_attempts() {
+        let (state, _) = state_with(Vec::new());
+        let started = Instant::now();
+
+        let result = polish_with_retries(&state, "text").await;
+        assert!(matches!(result, Err(PolishError::Timeout)));
+        assert_eq!(started.elapsed(), Duration::from_millis(3 * 5000 + 300));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_retry_permanent_failures() {
+        let (state, polisher) = state_with(vec![Ok("  ".to_string()), Ok("Polished.".to_string())]);
+        assert!(matches!(polish_with_retries(&state, "text").await, Err(PolishError::Empty)));
+        assert_eq!(polisher.unused(), 1);
+
+        let (state, polisher) = state_with(vec![Err(PolishError::Status(400)), Ok("Polished.".to_string())]);
+        assert!(matches!(polish_with_retries(&state, "text").await, Err(PolishError::Status(400))));
+        assert_eq!(polisher.unused(), 1);
+    }
+
+    #[tokio::test]
+    async fn discards_results_for_edited_entries() {
+        let (state, _) = state_with(Vec::new());
+        state.feedbacks.write().await.push(feedback("f1", "ada", "Edited text"));
+        let job = |content: &str| PolishJob {
+            target: PolishTarget::Feedback {
+                feedback_id: "f1".to_string(),
+            },
+            content: content.to_string(),
+        };
+
+        record_result(&state, &job("Original text"), Ok("Polished original.".to_string())).await;
+        assert_eq!(state.feedbacks.read().await[0].polish_status, PolishStatus::Skipped);
+        assert_eq!(state.feedbacks.read().await[0].polished_content, None);
+
+        record_result(&state, &job("Edited text"), Err(PolishError::Timeout)).await;
+        let failed = state.feedbacks.read().await[0].clone();
+        assert_eq!(failed.polish_status, PolishStatus::Failed);
+        assert_eq!(failed.polish_error.as_deref(), Some("timed out"));
+
+        record_result(&state, &job("Edited text"), Ok("Polished edit.".to_string())).await;
+        let polished = state.feedbacks.read().await[0].clone();
+        assert_eq!(polished.polish_status, PolishStatus::Succeeded);
+        assert_eq!(polished.polished_content.as_deref(), Some("Polished edit."));
+        assert_eq!(polished.polish_error, None);
+    }
+}
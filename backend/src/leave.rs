@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Months, NaiveDate};
+use uuid::Uuid;
+
+use crate::config::{Config, LeaveAccrual};
+use crate::models::{AbsenceType, LeaveTransaction, LeaveTransactionKind};
+
+/// Accrual progress of one user's balance for one absence type.
+#[derive(Debug, Clone)]
+struct LeaveAccount {
+    /// First month credited for this account
+    opened: NaiveDate,
+    /// First day of the next month to credit
+    next_period: NaiveDate,
+    /// Days brought over from last year that have not expired yet
+    carried_over: f64,
+    carry_over_expires_on: Option<NaiveDate>,
+}
+
+/// Leave balances, kept as a ledger of transactions. Balances are brought
+/// up to date lazily by `sync` whenever they are read or charged.
+#[derive(Debug, Default)]
+pub struct LeaveLedger {
+    accounts: HashMap<(String, AbsenceType), LeaveAccount>,
+    transactions: Vec<LeaveTransaction>,
+}
+
+impl LeaveLedger {
+    pub fn balance(&self, user_id: &str, absence_type: AbsenceType) -> f64 {
+        round_days(
+            self.transactions
+                .iter()
+                .filter(|t| t.user_id == user_id && t.absence_type == absence_type)
+                .map(|t| t.amount)
+                .sum(),
+        )
+    }
+
+    /// The balance from transactions effective before `date`, leaving out
+    /// absences booked for later.
+    fn balance_before(&self, user_id: &str, absence_type: AbsenceType, date: NaiveDate) -> f64 {
+        round_days(
+            self.transactions
+                .iter()
+                .filter(|t| t.user_id == user_id && t.absence_type == absence_type && t.effective_date < date)
+                .map(|t| t.amount)
+                .sum(),
+        )
+    }
+
+    /// Days carried over from last year and when they expire, if any.
+    pub fn carry_over(&self, user_id: &str, absence_type: AbsenceType) -> (f64, Option<NaiveDate>) {
+        self.accounts
+            .get(&(user_id.to_string(), absence_type))
+            .map(|a| (a.carried_over, a.carry_over_expires_on))
+            .unwrap_or((0.0, None))
+    }
+
+    /// A user's transactions, oldest first.
+    pub fn transactions(&self, user_id: &str) -> Vec<&LeaveTransaction> {
+        let mut list: Vec<&LeaveTransaction> = self
+            .transactions
+            .iter()
+            .filter(|t| t.user_id == user_id)
+            .collect();
+        list.sort_by_key(|t| (t.effective_date, t.created_at));
+        list
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn post(
+        &mut self,
+        user_id: &str,
+        absence_type: AbsenceType,
+        kind: LeaveTransactionKind,
+        amount: f64,
+        effective_date: NaiveDate,
+        absence_id: Option<&str>,
+        note: Option<String>,
+    ) -> LeaveTransaction {
+        let transaction = LeaveTransaction {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            absence_type,
+            kind,
+            amount: round_days(amount),
+            effective_date,
+            absence_id: absence_id.map(str::to_string),
+            note,
+            created_at: chrono::Utc::now(),
+        };
+        self.transactions.push(transaction.clone());
+        transaction
+    }
+
+    /// Charges an approved absence against the balance. The charge is
+    /// dated on the absence's first day, so carry-over expiry sees when
+    /// the days are taken rather than when they were approved.
+    pub fn deduct(&mut self, user_id: &str, absence_type: AbsenceType, days: f64, absence_id: &str, first_day: NaiveDate) {
+        self.post(
+            user_id,
+            absence_type,
+            LeaveTransactionKind::Deduction,
+            -days,
+            first_day,
+            Some(absence_id),
+            None,
+        );
+    }
+
+    /// Gives back whatever is still deducted for an absence, dated like the
+    /// deduction so the days no longer count as taken then.
+    pub fn restore(&mut self, absence_id: &str) {
+        let charged: Vec<&LeaveTransaction> = self
+            .transactions
+            .iter()
+            .filter(|t| t.absence_id.as_deref() == Some(absence_id))
+            .collect();
+        let Some(first) = charged.first() else { return };
+        let (user_id, absence_type, effective_date) = (first.user_id.clone(), first.absence_type, first.effective_date);
+        let net: f64 = charged.iter().map(|t| t.amount).sum();
+
+        if net < 0.0 {
+            self.post(
+                &user_id,
+                absence_type,
+                LeaveTransactionKind::Restoration,
+                -net,
+                effective_date,
+                Some(absence_id),
+                None,
+            );
+        }
+    }
+
+    /// Credits accruals, applies the year-end carry-over cap and expires
+    /// carried-over days for every month up to and including `today`.
+    pub fn sync(&mut self, config: &Config, user_id: &str, absence_type: AbsenceType, today: NaiveDate) {
+        let entitlement = config.leave_entitlements.get(&absence_type).copied().unwrap_or(0.0);
+        let key = (user_id.to_string(), absence_type);
+        // Users have no start date, so new accounts accrue from January
+        // of the current year
+        let mut account = self.accounts.get(&key).cloned().unwrap_or_else(|| {
+            let year_start = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap_or(today);
+            LeaveAccount {
+                opened: year_start,
+                next_period: year_start,
+                carried_over: 0.0,
+                carry_over_expires_on: None,
+            }
+        });
+
+        while account.next_period <= today {
+            let period = account.next_period;
+            self.expire_carry_over(user_id, absence_type, &mut account, period);
+
+            if period.month() == 1 && period != account.opened {
+                self.roll_over_year(config, user_id, absence_type, &mut account, period);
+            }
+
+            let accrual = period_accrual(config.leave_accrual, entitlement, period, period == account.opened);
+            if accrual > 0.0 {
+                self.post(
+                    user_id,
+                    absence_type,
+                    LeaveTransactionKind::Accrual,
+                    accrual,
+                    period,
+                    None,
+                    Some(format!("{}-{:02}", period.year(), period.month())),
+                );
+            }
+
+            account.next_period = period + Months::new(1);
+        }
+        self.expire_carry_over(user_id, absence_type, &mut account, today);
+
+        self.accounts.insert(key, account);
+    }
+
+    /// Unused days above the cap are forfeited on January 1st; the rest is
+    /// carried over until the configured expiry.
+    fn roll_over_year(
+        &mut self,
+        config: &Config,
+        user_id: &str,
+        absence_type: AbsenceType,
+        account: &mut LeaveAccount,
+        new_year: NaiveDate,
+    ) {
+        let remaining = self.balance_before(user_id, absence_type, new_year).max(0.0);
+        let cap = config.leave_carry_over_max_days.max(0.0);
+        if remaining > cap {
+            self.post(
+                user_id,
+                absence_type,
+                LeaveTransactionKind::Forfeit,
+                -(remaining - cap),
+                new_year,
+                None,
+                Some(format!("Above the {} day carry-over cap", cap)),
+            );
+        }
+
+        account.carried_over = remaining.min(cap);
+        account.carry_over_expires_on = (account.carried_over > 0.0)
+            .then(|| new_year + Months::new(config.leave_carry_over_expiry_months));
+    }
+
+    /// Once the expiry date is reached, carried-over days not used since
+    /// the start of the year are removed. Days taken are counted against
+    /// the carry-over first.
+    fn expire_carry_over(&mut self, user_id: &str, absence_type: AbsenceType, account: &mut LeaveAccount, date: NaiveDate) {
+        let Some(expires_on) = account.carry_over_expires_on else { return };
+        if expires_on > date {
+            return;
+        }
+
+        let year_start = NaiveDate::from_ymd_opt(expires_on.year(), 1, 1).unwrap_or(expires_on);
+        let used: f64 = -self
+            .transactions
+            .iter()
+            .filter(|t| {
+                t.user_id == user_id
+                    && t.absence_type == absence_type
+                    && matches!(t.kind, LeaveTransactionKind::Deduction | LeaveTransactionKind::Restoration)
+                    && t.effective_date >= year_start
+                    && t.effective_date < expires_on
+            })
+            .map(|t| t.amount)
+            .sum::<f64>();
+
+        let unused = account.carried_over - used.max(0.0);
+        if unused > 0.0 {
+            self.post(
+                user_id,
+                absence_type,
+                LeaveTransactionKind::Expiry,
+                -unused,
+                expires_on,
+                None,
+                Some("Carried-over days expired".to_string()),
+            );
+        }
+        account.carried_over = 0.0;
+        account.carry_over_expires_on = None;
+    }
+}
+
+/// Days credited for the month starting at `period`. Monthly accrual
+/// spreads the entitlement so the year adds up exactly; annual accrual
+/// credits everything in January, prorated for accounts opened mid-year.
+fn period_accrual(accrual: LeaveAccrual, entitlement: f64, period: NaiveDate, first_period: bool) -> f64 {
+    let month = f64::from(period.month());
+    match accrual {
+        LeaveAccrual::Monthly => {
+            round_days(entitlement * month / 12.0) - round_days(entitlement * (month - 1.0) / 12.0)
+        }
+        LeaveAccrual::Annual if first_period => round_days(entitlement * (13.0 - month) / 12.0),
+        LeaveAccrual::Annual if period.month() == 1 => entitlement,
+        LeaveAccrual::Annual => 0.0,
+    }
+}
+
+/// Rounds to hundredths of a day to keep sums free of float noise.
+pub fn round_days(days: f64) -> f64 {
+    // Adding zero turns -0.0 (e.g. an empty sum) into 0.0
+    (days * 100.0).round() / 100.0 + 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VACATION: AbsenceType = AbsenceType::Vacation;
+
+    /// 24 days a year, so 2 a month; at most 5 days carried over until April.
+    fn config(leave_accrual: LeaveAccrual) -> Config {
+        Config {
+            leave_entitlements: HashMap::from([(VACATION, 24.0)]),
+            leave_accrual,
+            leave_carry_over_max_days: 5.0,
+            leave_carry_over_expiry_months: 3,
+            ..Config::from_env()
+        }
+    }
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn amounts(ledger: &LeaveLedger, kind: LeaveTransactionKind) -> Vec<(NaiveDate, f64)> {
+        ledger
+            .transactions("ada")
+            .into_iter()
+            .filter(|t| t.kind == kind)
+            .map(|t| (t.effective_date, t.amount))
+            .collect()
+    }
+
+    #[test]
+    fn accrues_monthly_or_all_in_january() {
+        let mut monthly = LeaveLedger::default();
+        monthly.sync(&config(LeaveAccrual::Monthly), "ada", VACATION, day(2024, 3, 15));
+        assert_eq!(monthly.balance("ada", VACATION), 6.0);
+
+        // Syncing again does not credit the same months twice
+        monthly.sync(&config(LeaveAccrual::Monthly), "ada", VACATION, day(2024, 3, 20));
+        assert_eq!(monthly.balance("ada", VACATION), 6.0);
+
+        let mut annual = LeaveLedger::default();
+        annual.sync(&config(LeaveAccrual::Annual), "ada", VACATION, day(2024, 3, 15));
+        annual.sync(&config(LeaveAccrual::Annual), "ada", VACATION, day(2024, 12, 31));
+        assert_eq!(annual.balance("ada", VACATION), 24.0);
+    }
+
+    #[test]
+    fn caps_the_carry_over_at_the_year_boundary() {
+        let config = config(LeaveAccrual::Monthly);
+        let mut ledger = LeaveLedger::default();
+        ledger.sync(&config, "ada", VACATION, day(2024, 6, 1));
+        ledger.deduct("ada", VACATION, 2.0, "may", day(2024, 5, 6));
+        ledger.sync(&config, "ada", VACATION, day(2025, 1, 15));
+
+        // 22 days left at the end of 2024, of which 5 are carried over
+        assert_eq!(amounts(&ledger, LeaveTransactionKind::Forfeit), [(day(2025, 1, 1), -17.0)]);
+        assert_eq!(ledger.carry_over("ada", VACATION), (5.0, Some(day(2025, 4, 1))));
+        assert_eq!(ledger.balance("ada", VACATION), 7.0);
+    }
+
+    #[test]
+    fn expires_carried_over_days_not_taken_in_time() {
+        let config = config(LeaveAccrual::Monthly);
+        let mut ledger = LeaveLedger::default();
+        ledger.sync(&config, "ada", VACATION, day(2024, 12, 31));
+        // Approved in December for February: it uses up carried-over days,
+        // but does not count against the year-end cap
+        ledger.deduct("ada", VACATION, 3.0, "february", day(2025, 2, 3));
+        ledger.sync(&config, "ada", VACATION, day(2025, 3, 31));
+        assert_eq!(amounts(&ledger, LeaveTransactionKind::Forfeit), [(day(2025, 1, 1), -19.0)]);
+        assert!(amounts(&ledger, LeaveTransactionKind::Expiry).is_empty());
+
+        ledger.sync(&config, "ada", VACATION, day(2025, 4, 1));
+        assert_eq!(amounts(&ledger, LeaveTransactionKind::Expiry), [(day(2025, 4, 1), -2.0)]);
+        assert_eq!(ledger.carry_over("ada", VACATION), (0.0, None));
+        // 24 - 19 forfeited - 3 taken - 2 expired + 8 accrued in 2025
+        assert_eq!(ledger.balance("ada", VACATION), 8.0);
+    }
+
+    #[test]
+    fn restored_days_no_longer_count_as_taken() {
+        let config = config(LeaveAccrual::Monthly);
+        let mut ledger = LeaveLedger::default();
+        ledger.sync(&config, "ada", VACATION, day(2024, 12, 31));
+        ledger.deduct("ada", VACATION, 3.0, "february", day(2025, 2, 3));
+        ledger.restore("february");
+        ledger.restore("february");
+        assert_eq!(amounts(&ledger, LeaveTransactionKind::Restoration), [(day(2025, 2, 3), 3.0)]);
+        assert_eq!(ledger.balance("ada", VACATION), 24.0);
+
+        ledger.sync(&config, "ada", VACATION, day(2025, 4, 1));
+        assert_eq!(amounts(&ledger, LeaveTransactionKind::Expiry), [(day(2025, 4, 1), -5.0)]);
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing mail transport. Swap the implementation held in `AppState`
+/// to deliver through SMTP or a provider API.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: &MailMessage) -> anyhow::Result<()>;
+}
+
+/// Local stand-in that writes each message to a file in an outbox directory.
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, message: &MailMessage) -> anyhow::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        let now = Utc::now();
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            now.format("%Y%m%dT%H%M%S"),
+            Uuid::new_v4().simple()
+        ));
+        let contents = format!(
+            "Date: {}\nTo: {}\nSubject: {}\n\n{}\n",
+            now.to_rfc2822(),
+            message.to,
+            message.subject,
+            message.body
+        );
+        tokio::fs::write(&path, contents).await?;
+
+        tracing::info!("Mail to {} written to {}", message.to, path.display());
+        Ok(())
+    }
+}
@@ -1,13 +1,34 @@
+mod absence;
+mod approval;
 mod auth;
+mod avatar;
+mod calendar_feed;
+mod config;
+mod coverage;
+mod directory;
 mod handlers;
+mod holidays;
+mod jobs;
+mod leave;
+mod mailer;
 mod models;
+mod org;
+mod payroll;
+mod polish;
+mod review;
 mod migrations;
 mod routes;
+mod seal;
+mod templates;
+#[cfg(test)]
+mod test_support;
+mod tokens;
 mod state;
+mod storage;
 
 use axum::{
     http::Method,
-    routing::{get, post},
+    routing::get,
     Router,
 };
 use tower_http::cors::{CorsLayer, Any};
@@ -20,7 +41,9 @@ use crate::state::AppState;
 async fn main() {
     tracing_subscriber::fmt::init();
 
-    let state = AppState::new();
+    let (state, polish_jobs) = AppState::new();
+    tracing::info!("Using '{}' feedback polisher", state.polisher.name());
+    jobs::spawn_polish_worker(state.clone(), polish_jobs);
     
     // Run migrations to seed initial data
     run_migrations(&state).await;
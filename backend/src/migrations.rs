@@ -1,7 +1,7 @@
 use chrono::Utc;
 use tracing::info;
 
-use crate::models::{User, DataItem};
+use crate::models::{User, UserStatus, DataItem, WorkSchedule};
 use crate::state::AppState;
 
 pub async fn run_migrations(state: &AppState) {
@@ -35,6 +35,12 @@ async fn seed_default_users(state: &AppState) {
         email: "manager@newwork.com".to_string(),
         password_hash: password_hash.clone(),
         role: "manager".to_string(),
+        manager_id: None,
+        status: UserStatus::Active,
+        avatar_version: None,
+        location: None,
+        time_zone: None,
+        work_schedule: WorkSchedule::default(),
     };
     
     let user2 = User {
@@ -43,6 +49,12 @@ async fn seed_default_users(state: &AppState) {
         email: "employee@newwork.com".to_string(),
         password_hash: password_hash.clone(),
         role: "employee".to_string(),
+        manager_id: Some("1".to_string()),
+        status: UserStatus::Active,
+        avatar_version: None,
+        location: None,
+        time_zone: None,
+        work_schedule: WorkSchedule::default(),
     };
     
     let user3 = User {
@@ -51,6 +63,12 @@ async fn seed_default_users(state: &AppState) {
         email: "coworker@newwork.com".to_string(),
         password_hash,
         role: "coworker".to_string(),
+        manager_id: Some("1".to_string()),
+        status: UserStatus::Active,
+        avatar_version: None,
+        location: None,
+        time_zone: None,
+        work_schedule: WorkSchedule::default(),
     };
     
     users.insert(user1.email.clone(), user1);
@@ -1,13 +1,90 @@
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::seal::AuthorSealer;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
     pub name: String,
     pub email: String,
-    pub password_hash: String,
+    pub password_hash: String, // Empty until an invited user sets a password
     pub role: String, // "manager", "employee", "coworker"
+    #[serde(default)]
+    pub manager_id: Option<String>, // User ID of this user's line manager
+    #[serde(default)]
+    pub status: UserStatus,
+    #[serde(default)]
+    pub avatar_version: Option<String>, // Changes on every upload; None when no avatar is set
+    #[serde(default)]
+    pub location: Option<String>, // Selects the holiday calendar
+    #[serde(default)]
+    pub time_zone: Option<String>, // IANA name, e.g. "Europe/Berlin"; defaults to DEFAULT_TIME_ZONE
+    #[serde(default)]
+    pub work_schedule: WorkSchedule,
+}
+
+/// Share of a full working day worked on each weekday.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkSchedule {
+    pub monday: f64,
+    pub tuesday: f64,
+    pub wednesday: f64,
+    pub thursday: f64,
+    pub friday: f64,
+    pub saturday: f64,
+    pub sunday: f64,
+}
+
+impl Default for WorkSchedule {
+    fn default() -> Self {
+        Self {
+            monday: 1.0,
+            tuesday: 1.0,
+            wednesday: 1.0,
+            thursday: 1.0,
+            friday: 1.0,
+            saturday: 0.0,
+            sunday: 0.0,
+        }
+    }
+}
+
+impl WorkSchedule {
+    pub fn day(&self, weekday: chrono::Weekday) -> f64 {
+        match weekday {
+            chrono::Weekday::Mon => self.monday,
+            chrono::Weekday::Tue => self.tuesday,
+            chrono::Weekday::Wed => self.wednesday,
+            chrono::Weekday::Thu => self.thursday,
+            chrono::Weekday::Fri => self.friday,
+            chrono::Weekday::Sat => self.saturday,
+            chrono::Weekday::Sun => self.sunday,
+        }
+    }
+
+    /// Every day is between 0 and 1, and at least one day is worked.
+    pub fn is_valid(&self) -> bool {
+        let days = [
+            self.monday,
+            self.tuesday,
+            self.wednesday,
+            self.thursday,
+            self.friday,
+            self.saturday,
+            self.sunday,
+        ];
+        days.iter().all(|d| (0.0..=1.0).contains(d)) && days.iter().any(|d| *d > 0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    #[default]
+    Active,
+    Invited, // Created through an invitation that has not been accepted yet
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,62 +105,817 @@ pub struct UserInfo {
     pub name: String,
     pub email: String,
     pub role: String,
+    pub manager_id: Option<String>,
+    pub status: UserStatus,
+    pub avatar_url: Option<String>,
+    pub location: Option<String>,
+    pub time_zone: Option<String>,
+    pub work_schedule: WorkSchedule,
+}
+
+impl From<&User> for UserInfo {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id.clone(),
+            name: user.name.clone(),
+            email: user.email.clone(),
+            role: user.role.clone(),
+            manager_id: user.manager_id.clone(),
+            status: user.status,
+            avatar_url: user.avatar_version.as_ref().map(|version| {
+                format!("/api/users/{}/avatar?v={}", user.id, version)
+            }),
+            location: user.location.clone(),
+            time_zone: user.time_zone.clone(),
+            work_schedule: user.work_schedule,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateUserRequest {
     pub name: String,
+    pub email: String, // The user is invited here and sets their own password
+    pub role: String, // "manager", "employee", "coworker"
+    #[serde(default)]
+    pub manager_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AvatarQuery {
+    pub size: Option<u32>,
+    pub v: Option<String>, // Current avatar version, as in UserInfo::avatar_url
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invitation {
+    pub id: String,
+    pub user_id: String, // Pending user created alongside the invitation
     pub email: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String, // Hash of the one-time secret delivered by mail; the secret itself is never stored
+    pub invited_by: String, // Manager user ID
+    pub status: InvitationStatus,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub sent_count: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Revoked,
+    Expired, // Only reported in responses; stored invitations stay Pending
+}
+
+impl Invitation {
+    /// Status as seen by clients, accounting for expiry.
+    pub fn effective_status(&self, now: DateTime<Utc>) -> InvitationStatus {
+        if self.status == InvitationStatus::Pending && self.expires_at <= now {
+            InvitationStatus::Expired
+        } else {
+            self.status
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
     pub password: String,
-    pub role: String, // "manager", "employee", "coworker"
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportUsersQuery {
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRowError {
+    pub row: usize, // 1-based data row number (header excluded)
+    pub email: Option<String>,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportUsersResponse {
+    pub dry_run: bool,
+    pub imported: bool, // false when dry_run is set or any row failed validation
+    pub total_rows: usize,
+    pub errors: Vec<ImportRowError>,
+    pub users: Vec<UserInfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Feedback {
     pub id: String,
     pub user_id: String, // User ID the feedback is for
-    pub from_user_id: String, // User ID who created the feedback
+    pub from_user_id: Option<String>, // User ID who created the feedback, None when anonymous
+    #[serde(default)]
+    pub anonymous: bool,
+    #[serde(default, skip_serializing)]
+    pub sealed_author: Option<String>, // Encrypted author ID of anonymous feedback, never returned
     pub content: String,
+    #[serde(default)]
+    pub template_id: Option<String>, // Template the structured answers follow
+    #[serde(default)]
+    pub answers: Vec<FeedbackAnswer>,
     pub polished_content: Option<String>,
+    #[serde(default)]
+    pub polish_status: PolishStatus,
+    #[serde(default)]
+    pub polish_error: Option<String>, // Why polishing failed, when it did
+    #[serde(default)]
+    pub published_version: PublishedVersion, // Which text readers are shown
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>, // Set on the last author edit
+    #[serde(default)]
+    pub revisions: Vec<FeedbackRevision>, // Earlier versions, oldest first
+    #[serde(default)]
+    pub retracted_at: Option<DateTime<Utc>>, // Set when the author retracts
+    #[serde(default)]
+    pub moderation: Option<FeedbackModeration>, // Set when a manager hides the entry
+}
+
+impl Feedback {
+    /// Resolves authorship, opening the sealed author of anonymous entries.
+    pub fn is_written_by(&self, user_id: &str, sealer: &AuthorSealer) -> bool {
+        match (&self.from_user_id, &self.sealed_author) {
+            (Some(author), _) => author == user_id,
+            (None, Some(sealed)) => sealer.open(sealed).as_deref() == Some(user_id),
+            (None, None) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolishStatus {
+    #[default]
+    Skipped, // Polishing was not requested
+    Pending, // Queued for the background polish worker
+    Succeeded,
+    Failed, // See polish_error; polished_content stays empty
+}
+
+/// Version of a feedback entry shown to readers. When `Polished` is chosen
+/// but no polished text exists (yet), readers see the original.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishedVersion {
+    #[default]
+    Original,
+    Polished,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolishPreviewRequest {
+    pub content: String,
+}
+
+/// A suggestion issued by the polish preview. Authors accept it by its ID
+/// when creating feedback, so only text the polisher produced is stored as
+/// polished.
+#[derive(Debug, Clone)]
+pub struct PolishPreview {
+    pub author_id: String,
+    pub original: String,
+    pub suggested: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PolishPreviewResponse {
+    pub preview_id: String,
+    pub original: String,
+    pub suggested: String,
+    pub provider: String,
+    pub diff: Vec<DiffChunk>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A run of words that is unchanged, added or removed by polishing.
+#[derive(Debug, Serialize)]
+pub struct DiffChunk {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackRevision {
+    pub content: String,
+    pub polished_content: Option<String>,
+    pub replaced_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackModeration {
+    pub hidden_by: String, // Manager user ID
+    pub reason: String,
+    pub hidden_at: DateTime<Utc>,
+}
+
+/// Author edits, publishing, retraction and moderation, shared by
+/// `Feedback` and `DataItemFeedback`.
+pub trait Moderated {
+    fn created_at(&self) -> DateTime<Utc>;
+    fn retracted_at_mut(&mut self) -> &mut Option<DateTime<Utc>>;
+    fn moderation_mut(&mut self) -> &mut Option<FeedbackModeration>;
+    fn is_retracted(&self) -> bool;
+    fn is_hidden(&self) -> bool;
+
+    /// Replaces the text, keeping the superseded version. The old polish
+    /// no longer matches the text, so it moves to the revision as well.
+    fn revise(&mut self, content: String, now: DateTime<Utc>);
+
+    /// Leaves only the published text, for anyone but the author. Earlier
+    /// versions go too, as they may hold unpublished text.
+    fn publish_only(&mut self);
+
+    /// Retracted and hidden entries are left out of normal listings.
+    fn is_visible(&self) -> bool {
+        !self.is_retracted() && !self.is_hidden()
+    }
+}
+
+macro_rules! impl_moderated {
+    ($type:ty) => {
+        impl Moderated for $type {
+            fn created_at(&self) -> DateTime<Utc> {
+                self.created_at
+            }
+
+            fn retracted_at_mut(&mut self) -> &mut Option<DateTime<Utc>> {
+                &mut self.retracted_at
+            }
+
+            fn moderation_mut(&mut self) -> &mut Option<FeedbackModeration> {
+                &mut self.moderation
+            }
+
+            fn is_retracted(&self) -> bool {
+                self.retracted_at.is_some()
+            }
+
+            fn is_hidden(&self) -> bool {
+                self.moderation.is_some()
+            }
+
+            fn revise(&mut self, content: String, now: DateTime<Utc>) {
+                self.revisions.push(FeedbackRevision {
+                    content: std::mem::replace(&mut self.content, content),
+                    polished_content: self.polished_content.take(),
+                    replaced_at: now,
+                });
+                self.polish_status = PolishStatus::Skipped;
+                self.polish_error = None;
+                self.published_version = PublishedVersion::Original;
+                self.updated_at = Some(now);
+            }
+
+            fn publish_only(&mut self) {
+                let polished = self.polished_content.take();
+                if self.published_version == PublishedVersion::Polished {
+                    if let Some(polished) = polished {
+                        self.content = polished;
+                    }
+                }
+                self.revisions.clear();
+            }
+        }
+    };
+}
+
+impl_moderated!(Feedback);
+impl_moderated!(DataItemFeedback);
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateFeedbackRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HideFeedbackRequest {
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedbackListQuery {
+    pub page: Option<usize>, // 1-based, defaults to 1
+    pub per_page: Option<usize>, // Defaults to 20, capped at 100
+    pub from: Option<DateTime<Utc>>, // Inclusive lower bound on created_at
+    pub to: Option<DateTime<Utc>>, // Exclusive upper bound on created_at
+    pub include_hidden: Option<bool>, // Managers only: include retracted and hidden entries
+}
+
+#[derive(Debug, Serialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub page: usize,
+    pub per_page: usize,
+    pub total: usize,
+}
+
+impl<T> Paginated<T> {
+    pub const DEFAULT_PER_PAGE: usize = 20;
+    pub const MAX_PER_PAGE: usize = 100;
+
+    /// Slices an already filtered and sorted list into the requested page.
+    pub fn from_vec(items: Vec<T>, page: Option<usize>, per_page: Option<usize>) -> Self {
+        let page = page.unwrap_or(1).max(1);
+        let per_page = per_page
+            .unwrap_or(Self::DEFAULT_PER_PAGE)
+            .clamp(1, Self::MAX_PER_PAGE);
+        let total = items.len();
+        let items = items
+            .into_iter()
+            .skip((page - 1).saturating_mul(per_page))
+            .take(per_page)
+            .collect();
+
+        Self { items, page, per_page, total }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateFeedbackRequest {
     pub user_id: String, // User ID the feedback is for
-    pub content: String,
-    pub polish: Option<bool>,
+    #[serde(default)]
+    pub content: String, // Optional when answering a template
+    pub template_id: Option<String>,
+    #[serde(default)]
+    pub answers: Vec<FeedbackAnswer>,
+    pub polish: Option<bool>, // Polish in the background after saving
+    pub preview_id: Option<String>, // Accept the suggestion of this polish preview
+    pub publish: Option<PublishedVersion>, // Defaults to polished when any polish is involved
+    pub anonymous: Option<bool>, // Hide the author from recipients and managers
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub sections: Vec<TemplateSection>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub archived: bool, // Archived templates cannot be used for new feedback
+}
+
+impl FeedbackTemplate {
+    pub fn questions(&self) -> impl Iterator<Item = &TemplateQuestion> + Clone {
+        self.sections.iter().flat_map(|s| s.questions.iter())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSection {
+    pub title: String,
+    pub questions: Vec<TemplateQuestion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateQuestion {
+    pub id: String,
+    pub text: String,
+    pub required: bool,
+    #[serde(flatten)]
+    pub kind: TemplateQuestionKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TemplateQuestionKind {
+    Numeric { min: i32, max: i32 },
+    Likert { labels: Vec<String> }, // Answered with the 1-based position of a label
+    Text,
+}
+
+impl TemplateQuestionKind {
+    /// Lowest and highest rating of a rated question, None for text.
+    pub fn scale(&self) -> Option<(i32, i32)> {
+        match self {
+            TemplateQuestionKind::Numeric { min, max } => Some((*min, *max)),
+            TemplateQuestionKind::Likert { labels } => Some((1, labels.len() as i32)),
+            TemplateQuestionKind::Text => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackAnswer {
+    pub question_id: String,
+    pub rating: Option<i32>, // Numeric and Likert questions
+    pub text: Option<String>, // Text questions
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFeedbackTemplateRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub sections: Vec<TemplateSectionRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplateSectionRequest {
+    pub title: String,
+    pub questions: Vec<TemplateQuestionRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplateQuestionRequest {
+    pub text: String,
+    pub required: Option<bool>, // Defaults to true
+    #[serde(flatten)]
+    pub kind: TemplateQuestionKind,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedbackTemplateQuery {
+    pub include_archived: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AbsenceRequest {
     pub id: String,
     pub user_id: String, // User ID who requested the absence
+    #[serde(default)]
+    pub absence_type: AbsenceType,
     pub start_date: DateTime<Utc>,
     pub end_date: DateTime<Utc>,
+    #[serde(default)]
+    pub start_half_day: bool, // Starts at midday on the first day
+    #[serde(default)]
+    pub end_half_day: bool, // Ends at midday on the last day
+    #[serde(default)]
+    pub working_days: f64, // Working days charged, fixed when the request is filed
     pub reason: String,
+    #[serde(default)]
+    pub documentation: Option<String>, // Reference to supporting documents, e.g. a sick note
     pub status: AbsenceStatus,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub decided_by: Option<String>, // Manager who approved or rejected the request, or SYSTEM_ACTOR
+    #[serde(default)]
+    pub decided_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub decision_comment: Option<String>,
+    #[serde(default)]
+    pub history: Vec<AbsenceStatusChange>, // Every status the request has had, oldest first
+    #[serde(default)]
+    pub approvals: Vec<ApprovalStep>, // Approval chain, decided in order
 }
 
+/// Who may decide an approval step.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "approver", rename_all = "snake_case")]
+pub enum Approver {
+    LineManager,              // The requester's manager
+    User { user_id: String }, // A named person, e.g. from HR
+    AnyManager,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalStep {
+    #[serde(flatten)]
+    pub approver: Approver,
+    pub assigned_to: Option<String>, // Resolved approver; None when any manager may decide
+    pub status: ApprovalStepStatus,
+    pub decided_by: Option<String>,
+    pub on_behalf_of: Option<String>, // Set when a delegate decided for the assigned approver
+    pub decided_at: Option<DateTime<Utc>>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
+pub enum ApprovalStepStatus {
+    Waiting,
+    Approved,
+    Rejected,
+    Skipped, // Not reached because an earlier step rejected
+}
+
+/// Approval chain for absences matching a type and minimum length. Rules
+/// are tried in order; the first match applies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    pub absence_type: Option<AbsenceType>, // None matches every type
+    pub min_working_days: Option<f64>,     // None matches every length
+    pub steps: Vec<Approver>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delegation {
+    pub id: String,
+    pub delegator_id: String,
+    pub delegate_id: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate, // Inclusive
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDelegationRequest {
+    pub delegate_id: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+/// Recorded as the actor of status changes nobody made by hand, such as
+/// approving absence types that need no approval.
+pub const SYSTEM_ACTOR: &str = "system";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbsenceStatusChange {
+    pub status: AbsenceStatus,
+    pub changed_by: String,
+    pub changed_at: DateTime<Utc>,
+    pub comment: Option<String>,
+}
+
+impl AbsenceRequest {
+    /// Moves to `status` and records the change in `history`.
+    pub fn set_status(&mut self, status: AbsenceStatus, changed_by: &str, comment: Option<String>) {
+        self.history.push(AbsenceStatusChange {
+            status: status.clone(),
+            changed_by: changed_by.to_string(),
+            changed_at: Utc::now(),
+            comment,
+        });
+        self.status = status;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AbsenceStatus {
     Pending,
     Approved,
     Rejected,
+    Withdrawn,             // Pending request taken back by the employee
+    CancellationRequested, // Employee asked to cancel an approved absence
+    Cancelled,             // Approved absence called off, days given back
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbsenceType {
+    #[default]
+    Vacation,
+    Sick,
+    Parental,
+    Unpaid,
+    Training,
+    Remote,
+}
+
+impl AbsenceType {
+    pub const ALL: [AbsenceType; 6] = [
+        AbsenceType::Vacation,
+        AbsenceType::Sick,
+        AbsenceType::Parental,
+        AbsenceType::Unpaid,
+        AbsenceType::Training,
+        AbsenceType::Remote,
+    ];
+}
+
+impl AbsenceStatus {
+    /// Requests that are, or may still become, time off block the dates
+    /// they cover.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            AbsenceStatus::Pending | AbsenceStatus::Approved | AbsenceStatus::CancellationRequested
+        )
+    }
+
+    /// Status changes a manager may make: deciding pending requests,
+    /// confirming or declining cancellation requests, and cancelling
+    /// approved absences directly.
+    pub fn manager_can_move_to(&self, next: &AbsenceStatus) -> bool {
+        matches!(
+            (self, next),
+            (AbsenceStatus::Pending, AbsenceStatus::Approved)
+                | (AbsenceStatus::Pending, AbsenceStatus::Rejected)
+                | (AbsenceStatus::CancellationRequested, AbsenceStatus::Cancelled)
+                | (AbsenceStatus::CancellationRequested, AbsenceStatus::Approved)
+                | (AbsenceStatus::Approved, AbsenceStatus::Cancelled)
+        )
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateAbsenceRequest {
+    #[serde(default)]
+    pub absence_type: AbsenceType, // Defaults to vacation
     pub start_date: DateTime<Utc>,
     pub end_date: DateTime<Utc>,
+    #[serde(default)]
+    pub start_half_day: bool,
+    #[serde(default)]
+    pub end_half_day: bool,
     pub reason: String,
+    pub documentation: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkingDaysQuery {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(default)]
+    pub start_half_day: bool,
+    #[serde(default)]
+    pub end_half_day: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AbsenceCalendarQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate, // Inclusive
+    pub manager_id: Option<String>, // Team reporting to this manager; defaults to the caller's team
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub absences: Vec<CalendarEntry>,
+    pub present: usize, // Team members without approved leave that day
+    pub below_minimum: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarEntry {
+    pub absence_id: String,
+    pub user_id: String,
+    pub absence_type: Option<AbsenceType>, // Only shown to managers and the absent user
+    pub status: AbsenceStatus,
+    pub half_day: bool,
+}
+
+/// A working day on which approving a request would leave too few people.
+#[derive(Debug, Serialize)]
+pub struct CoverageConflict {
+    pub date: NaiveDate,
+    pub present: usize,
+    pub minimum: usize,
+    pub absent_user_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AbsenceListQuery {
+    pub page: Option<usize>, // 1-based, defaults to 1
+    pub per_page: Option<usize>, // Defaults to 20, capped at 100
+    pub status: Option<AbsenceStatus>,
+    pub absence_type: Option<AbsenceType>,
+    pub user_id: Option<String>,
+    pub from: Option<NaiveDate>, // Absences ending on or after this day
+    pub to: Option<NaiveDate>, // Absences starting on or before this day
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AbsenceExportQuery {
+    pub from: NaiveDate, // First day of the pay period
+    pub to: NaiveDate, // Last day of the pay period
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// The part of an approved absence that falls in a pay period.
+#[derive(Debug, Clone, Serialize)]
+pub struct PayrollEntry {
+    pub user_id: String,
+    pub name: String,
+    pub email: String,
+    pub absence_id: String,
+    pub absence_type: AbsenceType,
+    pub first_day: NaiveDate,
+    pub last_day: NaiveDate,
+    pub working_days: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PayrollTotal {
+    pub user_id: String,
+    pub name: String,
+    pub email: String,
+    pub absence_type: AbsenceType,
+    pub absences: usize,
+    pub working_days: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarFeed {
+    pub user_id: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String, // Hash of the secret part of the feed URL; calendar clients cannot send a JWT
+    pub scope: CalendarFeedScope,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Whose approved absences a calendar feed lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarFeedScope {
+    #[default]
+    #[serde(rename = "self")]
+    Own,
+    Team,
+    Org, // Managers only
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCalendarFeedRequest {
+    #[serde(default)]
+    pub scope: CalendarFeedScope,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolidayCalendar {
+    pub location: String,
+    pub name: Option<String>,
+    pub holidays: Vec<Holiday>, // Sorted by date
+    pub imported_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaveTransaction {
+    pub id: String,
+    pub user_id: String,
+    pub absence_type: AbsenceType,
+    pub kind: LeaveTransactionKind,
+    pub amount: f64, // Days credited (positive) or debited (negative)
+    pub effective_date: NaiveDate,
+    pub absence_id: Option<String>, // Absence request behind a deduction or restoration
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaveTransactionKind {
+    Accrual,     // Entitlement credited for a period
+    Forfeit,     // Unused days above the carry-over cap at year end
+    Expiry,      // Carried-over days not used before they expired
+    Deduction,   // Approved absence
+    Restoration, // Deducted days given back
+    Adjustment,  // Manual correction by a manager
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaveBalance {
+    pub absence_type: AbsenceType,
+    pub annual_entitlement: f64,
+    pub balance: f64,   // Days credited minus days deducted
+    pub pending: f64,   // Days in requests awaiting a decision
+    pub available: f64, // Balance minus pending days
+    pub carried_over: f64,
+    pub carry_over_expires_on: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaveLedgerQuery {
+    pub absence_type: Option<AbsenceType>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaveAdjustmentRequest {
+    pub absence_type: AbsenceType,
+    pub amount: f64,
+    pub note: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateAbsenceStatusRequest {
     pub status: AbsenceStatus,
+    pub comment: Option<String>, // Required when rejecting
+    pub override_coverage: Option<bool>, // Approve even if team coverage drops below the minimum
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -91,15 +923,45 @@ pub struct UpdateUserRequest {
     pub name: Option<String>,
     pub email: Option<String>,
     pub role: Option<String>,
+    pub manager_id: Option<String>, // Empty string removes the manager
+    pub location: Option<String>, // Empty string removes the location
+    pub time_zone: Option<String>, // Empty string falls back to the default
+    pub work_schedule: Option<WorkSchedule>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataItemFeedback {
     pub id: String,
+    #[serde(default)]
+    pub parent_id: Option<String>, // Feedback this entry replies to, None for top-level feedback
     pub from_user_id: String, // User ID who created the feedback
     pub content: String,
     pub polished_content: Option<String>,
+    #[serde(default)]
+    pub polish_status: PolishStatus,
+    #[serde(default)]
+    pub polish_error: Option<String>, // Why polishing failed, when it did
+    #[serde(default)]
+    pub published_version: PublishedVersion, // Which text readers are shown
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>, // Set on the last author edit
+    #[serde(default)]
+    pub revisions: Vec<FeedbackRevision>, // Earlier versions, oldest first
+    #[serde(default)]
+    pub retracted_at: Option<DateTime<Utc>>, // Set when the author retracts
+    #[serde(default)]
+    pub moderation: Option<FeedbackModeration>, // Set when a manager hides the entry
+}
+
+/// Data item feedback with its replies, as returned by the data item
+/// endpoints.
+#[derive(Debug, Serialize)]
+pub struct DataItemFeedbackNode {
+    #[serde(flatten)]
+    pub feedback: DataItemFeedback,
+    pub removed: bool, // Retracted or hidden, kept empty so its replies stay in place
+    pub replies: Vec<DataItemFeedbackNode>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,9 +990,104 @@ pub struct UpdateDataItemRequest {
     pub is_deleted: Option<bool>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DataItemQuery {
+    pub include_hidden: Option<bool>, // Managers only: include retracted and hidden feedback
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AddDataItemFeedbackRequest {
     pub content: String,
-    pub polish: Option<bool>,
+    pub parent_id: Option<String>, // Reply to an existing feedback entry on the same item
+    pub polish: Option<bool>, // Polish in the background after saving
+    pub preview_id: Option<String>, // Accept the suggestion of this polish preview
+    pub publish: Option<PublishedVersion>, // Defaults to polished when any polish is involved
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewCycle {
+    pub id: String,
+    pub name: String,
+    pub created_by: String, // Manager who opened the cycle
+    pub deadline: DateTime<Utc>,
+    pub template_id: String,
+    pub sections: Vec<TemplateSection>, // Copied from the template when the cycle opens
+    pub assignments: Vec<ReviewAssignment>,
+    pub status: ReviewCycleStatus,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+impl ReviewCycle {
+    pub fn questions(&self) -> impl Iterator<Item = &TemplateQuestion> + Clone {
+        self.sections.iter().flat_map(|s| s.questions.iter())
+    }
+
+    /// Status as seen by clients; an open cycle past its deadline is closed.
+    pub fn effective_status(&self, now: DateTime<Utc>) -> ReviewCycleStatus {
+        if self.status == ReviewCycleStatus::Open && self.deadline <= now {
+            ReviewCycleStatus::Closed
+        } else {
+            self.status
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewCycleStatus {
+    Open,     // Collecting responses
+    Closed,   // Responses locked
+    Released, // Results visible to the reviewed employees
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewAssignment {
+    pub id: String,
+    pub employee_id: String, // User being reviewed
+    pub reviewer_id: String,
+    pub answers: Vec<FeedbackAnswer>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateReviewCycleRequest {
+    pub name: String,
+    pub deadline: DateTime<Utc>,
+    pub template_id: String, // Feedback template whose questions reviewers answer
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignReviewersRequest {
+    pub employee_id: String,
+    pub reviewer_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitReviewRequest {
+    pub answers: Vec<FeedbackAnswer>,
+}
+
+/// Anonymous per-question summary of the responses about one employee.
+#[derive(Debug, Serialize)]
+pub struct ReviewResults {
+    pub cycle_id: String,
+    pub employee_id: String,
+    pub reviewers: usize,
+    pub responses: usize,
+    pub withheld: bool, // Too few responses to show answers without identifying reviewers
+    pub questions: Vec<ReviewQuestionResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewQuestionResult {
+    pub question_id: String,
+    pub text: String,
+    #[serde(flatten)]
+    pub kind: TemplateQuestionKind,
+    pub average_rating: Option<f64>,
+    pub rating_counts: Option<Vec<usize>>, // Answers per scale value from the lowest, rated questions only
+    pub comments: Vec<String>,
+}
@@ -0,0 +1,244 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::models::{User, UserInfo, UserStatus};
+
+/// Users keyed by ID. The user store is keyed by email, so hierarchy
+/// queries build this view first.
+pub type UsersById<'a> = HashMap<&'a str, &'a User>;
+
+pub fn index_by_id(users: &HashMap<String, User>) -> UsersById<'_> {
+    users.values().map(|u| (u.id.as_str(), u)).collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrgNode {
+    pub user: OrgEntry,
+    pub reports: Vec<OrgNode>,
+}
+
+/// A user as shown by the org endpoints: managers see the full profile,
+/// everyone else only who is where in the hierarchy.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum OrgEntry {
+    Full(UserInfo),
+    Brief(OrgMember),
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrgMember {
+    pub id: String,
+    pub name: String,
+    pub manager_id: Option<String>,
+}
+
+pub fn entry(user: &User, full: bool) -> OrgEntry {
+    if full {
+        OrgEntry::Full(UserInfo::from(user))
+    } else {
+        OrgEntry::Brief(OrgMember {
+            id: user.id.clone(),
+            name: user.name.clone(),
+            manager_id: user.manager_id.clone(),
+        })
+    }
+}
+
+/// Returns true if making `manager_id` the manager of `user_id` would
+/// close a loop, i.e. `user_id` already appears in the manager's chain.
+pub fn would_create_cycle(by_id: &UsersById, user_id: &str, manager_id: &str) -> bool {
+    let mut current = Some(manager_id);
+    let mut visited = HashSet::new();
+    while let Some(id) = current {
+        if id == user_id || !visited.insert(id) {
+            return true;
+        }
+        current = by_id.get(id).and_then(|u| u.manager_id.as_deref());
+    }
+    false
+}
+
+/// Managers above `user_id`, nearest first.
+pub fn management_chain<'a>(by_id: &UsersById<'a>, user_id: &str) -> Vec<&'a User> {
+    let mut chain = Vec::new();
+    let mut visited = HashSet::from([user_id]);
+    let mut current = by_id.get(user_id).and_then(|u| u.manager_id.as_deref());
+    while let Some(id) = current {
+        // Guard against loops in stored data
+        if !visited.insert(id) {
+            break;
+        }
+        let Some(manager) = by_id.get(id) else { break };
+        chain.push(*manager);
+        current = manager.manager_id.as_deref();
+    }
+    chain
+}
+
+/// Two users share a team when they have the same manager or one
+/// directly manages the other.
+pub fn same_team(by_id: &UsersById, a: &str, b: &str) -> bool {
+    let manager_of = |id: &str| by_id.get(id).and_then(|u| u.manager_id.as_deref());
+    match (manager_of(a), manager_of(b)) {
+        (Some(ma), Some(mb)) if ma == mb => true,
+        (ma, mb) => ma == Some(b) || mb == Some(a),
+    }
+}
+
+/// Active users sharing `user_id`'s manager, including `user_id`, sorted
+/// by name. Users without a manager are a team of their own.
+pub fn team_of<'a>(by_id: &UsersById<'a>, user_id: &str) -> Vec<&'a User> {
+    let Some(user) = by_id.get(user_id) else { return Vec::new() };
+    if user.manager_id.is_none() {
+        return vec![*user];
+    }
+    let mut team: Vec<&User> = by_id
+        .values()
+        .filter(|u| u.manager_id == user.manager_id && u.status == UserStatus::Active)
+        .copied()
+        .collect();
+    team.sort_by(|a, b| a.name.cmp(&b.name));
+    team
+}
+
+/// Users whose manager is `user_id`, sorted by name.
+pub fn direct_reports<'a>(by_id: &UsersById<'a>, user_id: &str) -> Vec<&'a User> {
+    let mut reports: Vec<&User> = by_id
+        .values()
+        .filter(|u| u.manager_id.as_deref() == Some(user_id))
+        .copied()
+        .collect();
+    reports.sort_by(|a, b| a.name.cmp(&b.name));
+    reports
+}
+
+/// Builds the full hierarchy. Users without a (known) manager are roots.
+pub fn org_tree(by_id: &UsersById, full: bool) -> Vec<OrgNode> {
+    let mut roots: Vec<&User> = by_id
+        .values()
+        .filter(|u| {
+            u.manager_id
+                .as_deref()
+                .is_none_or(|m| !by_id.contains_key(m))
+        })
+        .copied()
+        .collect();
+    roots.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // Index reports once rather than scanning all users for every node
+    let mut reports_of: HashMap<&str, Vec<&User>> = HashMap::new();
+    for user in by_id.values() {
+        if let Some(manager_id) = user.manager_id.as_deref() {
+            reports_of.entry(manager_id).or_default().push(user);
+        }
+    }
+    for reports in reports_of.values_mut() {
+        reports.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    let mut visited = HashSet::new();
+    roots
+        .into_iter()
+        .map(|root| build_node(&reports_of, root, full, &mut visited))
+        .collect()
+}
+
+fn build_node<'a>(
+    reports_of: &HashMap<&str, Vec<&'a User>>,
+    user: &'a User,
+    full: bool,
+    visited: &mut HashSet<&'a str>,
+) -> OrgNode {
+    visited.insert(user.id.as_str());
+    let mut reports = Vec::new();
+    for report in reports_of.get(user.id.as_str()).into_iter().flatten() {
+        if !visited.contains(report.id.as_str()) {
+            reports.push(build_node(reports_of, report, full, visited));
+        }
+    }
+
+    OrgNode {
+        user: entry(user, full),
+        reports,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{user, user_store};
+
+    fn reports_to(id: &str, manager_id: &str) -> User {
+        User {
+            manager_id: Some(manager_id.to_string()),
+            ..user(id)
+        }
+    }
+
+    fn ids(users: &[&User]) -> Vec<String> {
+        users.iter().map(|u| u.id.clone()).collect()
+    }
+
+    #[test]
+    fn refuses_self_management_and_two_node_cycles() {
+        let users = user_store([user("boss"), reports_to("ada", "boss")]);
+        let by_id = index_by_id(&users);
+
+        assert!(would_create_cycle(&by_id, "ada", "ada"));
+        assert!(would_create_cycle(&by_id, "boss", "ada"));
+        assert!(!would_create_cycle(&by_id, "ada", "boss"));
+    }
+
+    #[test]
+    fn follows_deep_chains() {
+        let users = user_store(
+            std::iter::once(user("u0")).chain((1..100).map(|i| reports_to(&format!("u{}", i), &format!("u{}", i - 1)))),
+        );
+        let by_id = index_by_id(&users);
+
+        assert!(would_create_cycle(&by_id, "u0", "u99"));
+        assert!(would_create_cycle(&by_id, "u50", "u99"));
+        assert!(!would_create_cycle(&by_id, "u99", "u50"));
+        assert!(!would_create_cycle(&by_id, "new", "u99"));
+
+        let chain = management_chain(&by_id, "u99");
+        assert_eq!(chain.len(), 99);
+        assert_eq!((chain[0].id.as_str(), chain[98].id.as_str()), ("u98", "u0"));
+    }
+
+    #[test]
+    fn treats_missing_managers_as_the_top() {
+        let users = user_store([reports_to("ada", "ghost"), reports_to("bob", "ada")]);
+        let by_id = index_by_id(&users);
+
+        assert!(!would_create_cycle(&by_id, "bob", "ghost"));
+        assert!(would_create_cycle(&by_id, "ada", "bob"));
+        assert_eq!(ids(&management_chain(&by_id, "bob")), ["ada"]);
+        assert!(management_chain(&by_id, "ada").is_empty());
+
+        let tree = org_tree(&by_id, false);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].reports.len(), 1);
+    }
+
+    #[test]
+    fn stops_at_loops_in_stored_data() {
+        let users = user_store([
+            reports_to("ada", "bob"),
+            reports_to("bob", "ada"),
+            reports_to("cy", "ada"),
+            user("boss"),
+        ]);
+        let by_id = index_by_id(&users);
+
+        assert!(would_create_cycle(&by_id, "new", "cy"));
+        assert_eq!(ids(&management_chain(&by_id, "cy")), ["ada", "bob"]);
+        assert_eq!(ids(&management_chain(&by_id, "ada")), ["bob"]);
+
+        let tree = org_tree(&by_id, false);
+        assert_eq!(tree.len(), 1);
+        assert!(tree[0].reports.is_empty());
+    }
+}
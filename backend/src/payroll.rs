@@ -0,0 +1,219 @@
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::absence;
+use crate::leave::round_days;
+use crate::models::{AbsenceRequest, AbsenceStatus, AbsenceType, Holiday, PayrollEntry, PayrollTotal, User};
+
+/// Absences payroll pays out: approved ones, including those whose
+/// cancellation has been asked for but not yet confirmed.
+pub fn is_payable(status: &AbsenceStatus) -> bool {
+    matches!(status, AbsenceStatus::Approved | AbsenceStatus::CancellationRequested)
+}
+
+/// The part of `absence` that falls between `from` and `to`, or None if it
+/// is not payable or lies outside the period. Payroll charges the working
+/// days stored when the absence was filed, as the leave ledger does;
+/// absences crossing the period's bounds get the share of them that the
+/// days inside the period make up.
+pub fn entry(
+    absence: &AbsenceRequest,
+    user: &User,
+    time_zone: Tz,
+    holidays: &[Holiday],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Option<PayrollEntry> {
+    if !is_payable(&absence.status) {
+        return None;
+    }
+
+    let (first, last) = absence::local_dates(absence, time_zone);
+    if last < from || first > to {
+        return None;
+    }
+
+    let working_days = if first >= from && last <= to {
+        absence.working_days
+    } else {
+        let count = |first_day, last_day, start_half_day, end_half_day| {
+            absence::working_days(first_day, last_day, start_half_day, end_half_day, &user.work_schedule, holidays)
+        };
+        let whole = count(first, last, absence.start_half_day, absence.end_half_day);
+        let inside = count(
+            first.max(from),
+            last.min(to),
+            absence.start_half_day && first >= from,
+            absence.end_half_day && last <= to,
+        );
+        if whole > 0.0 {
+            round_days(absence.working_days * inside / whole)
+        } else {
+            0.0
+        }
+    };
+
+    Some(PayrollEntry {
+        user_id: user.id.clone(),
+        name: user.name.clone(),
+        email: user.email.clone(),
+        absence_id: absence.id.clone(),
+        absence_type: absence.absence_type,
+        first_day: first.max(from),
+        last_day: last.min(to),
+        working_days,
+    })
+}
+
+/// Sums entries per user and absence type, in the entries' order. Remote
+/// work is not leave and has no total.
+pub fn totals(entries: &[PayrollEntry]) -> Vec<PayrollTotal> {
+    let mut totals: Vec<PayrollTotal> = Vec::new();
+    for entry in entries.iter().filter(|e| absence::policy(e.absence_type).is_leave) {
+        match totals
+            .iter_mut()
+            .find(|t| t.user_id == entry.user_id && t.absence_type == entry.absence_type)
+        {
+            Some(total) => {
+                total.absences += 1;
+                total.working_days = round_days(total.working_days + entry.working_days);
+            }
+            None => totals.push(PayrollTotal {
+                user_id: entry.user_id.clone(),
+                name: entry.name.clone(),
+                email: entry.email.clone(),
+                absence_type: entry.absence_type,
+                absences: 1,
+                working_days: entry.working_days,
+            }),
+        }
+    }
+    totals
+}
+
+/// A line of the CSV export: an absence, or, with `absence_id` empty, the
+/// person's total for one absence type, as in `totals`.
+#[derive(Debug, Serialize)]
+pub struct CsvRow<'a> {
+    pub user_id: &'a str,
+    pub name: &'a str,
+    pub email: &'a str,
+    pub absence_id: &'a str,
+    pub absence_type: AbsenceType,
+    pub first_day: Option<NaiveDate>,
+    pub last_day: Option<NaiveDate>,
+    pub absences: usize,
+    pub working_days: f64,
+}
+
+/// Each person's entries followed by their totals. Expects the entries
+/// grouped by user.
+pub fn csv_rows<'a>(entries: &'a [PayrollEntry], totals: &'a [PayrollTotal]) -> Vec<CsvRow<'a>> {
+    let mut rows = Vec::new();
+    for group in entries.chunk_by(|a, b| a.user_id == b.user_id) {
+        rows.extend(group.iter().map(|entry| CsvRow {
+            user_id: &entry.user_id,
+            name: &entry.name,
+            email: &entry.email,
+            absence_id: &entry.absence_id,
+            absence_type: entry.absence_type,
+            first_day: Some(entry.first_day),
+            last_day: Some(entry.last_day),
+            absences: 1,
+            working_days: entry.working_days,
+        }));
+        rows.extend(totals.iter().filter(|t| t.user_id == group[0].user_id).map(|total| CsvRow {
+            user_id: &total.user_id,
+            name: &total.name,
+            email: &total.email,
+            absence_id: "",
+            absence_type: total.absence_type,
+            first_day: None,
+            last_day: None,
+            absences: total.absences,
+            working_days: total.working_days,
+        }));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, date, user};
+
+    /// Starting and ending at midday, charged `working_days` when filed.
+    fn absence(first: u32, last: u32, working_days: f64, status: AbsenceStatus) -> AbsenceRequest {
+        AbsenceRequest {
+            start_half_day: true,
+            end_half_day: true,
+            working_days,
+            ..test_support::absence("ada", first, last, status)
+        }
+    }
+
+    fn of_type(absence_type: AbsenceType, absence: AbsenceRequest) -> AbsenceRequest {
+        AbsenceRequest { absence_type, ..absence }
+    }
+
+    fn entry_in(absence: &AbsenceRequest, from: u32, to: u32) -> Option<PayrollEntry> {
+        entry(absence, &user("ada"), chrono_tz::UTC, &[], date(from), date(to))
+    }
+
+    #[test]
+    fn charges_the_stored_working_days() {
+        // Counted before a holiday was added, so more than a recount gives
+        let inside = entry_in(&absence(2, 4, 2.5, AbsenceStatus::Approved), 1, 31).unwrap();
+        assert_eq!((inside.first_day, inside.last_day), (date(2), date(4)));
+        assert_eq!(inside.working_days, 2.5);
+    }
+
+    #[test]
+    fn splits_the_stored_days_between_periods() {
+        // Wednesday to the next Tuesday, starting and ending at midday: 2.5
+        // of 4 working days fall in the first week
+        let crossing = absence(3, 9, 2.0, AbsenceStatus::Approved);
+        let before = entry_in(&crossing, 1, 5).unwrap();
+        let after = entry_in(&crossing, 6, 31).unwrap();
+
+        assert_eq!((before.first_day, before.last_day), (date(3), date(5)));
+        assert_eq!(before.working_days, 1.25);
+        assert_eq!((after.first_day, after.last_day), (date(6), date(9)));
+        assert_eq!(after.working_days, 0.75);
+    }
+
+    #[test]
+    fn skips_unpaid_statuses_and_other_periods() {
+        assert!(entry_in(&absence(2, 4, 2.0, AbsenceStatus::Pending), 1, 31).is_none());
+        assert!(entry_in(&absence(2, 4, 2.0, AbsenceStatus::Cancelled), 1, 31).is_none());
+        assert!(entry_in(&absence(2, 4, 2.0, AbsenceStatus::CancellationRequested), 1, 31).is_some());
+        assert!(entry_in(&absence(2, 4, 2.0, AbsenceStatus::Approved), 5, 31).is_none());
+    }
+
+    #[test]
+    fn totals_leave_per_person_and_type() {
+        let entries: Vec<PayrollEntry> = [
+            absence(2, 4, 2.0, AbsenceStatus::Approved),
+            of_type(AbsenceType::Sick, absence(8, 8, 1.0, AbsenceStatus::Approved)),
+            absence(10, 11, 1.0, AbsenceStatus::Approved),
+            of_type(AbsenceType::Remote, absence(15, 19, 4.0, AbsenceStatus::Approved)),
+        ]
+        .iter()
+        .filter_map(|a| entry_in(a, 1, 31))
+        .collect();
+        let totals = totals(&entries);
+
+        let summary: Vec<_> = totals.iter().map(|t| (t.absence_type, t.absences, t.working_days)).collect();
+        assert_eq!(summary, [(AbsenceType::Vacation, 2, 3.0), (AbsenceType::Sick, 1, 1.0)]);
+
+        // The CSV lists the same totals after the person's absences
+        let rows = csv_rows(&entries, &totals);
+        assert_eq!(rows.len(), 6);
+        let csv_totals: Vec<_> = rows[4..]
+            .iter()
+            .map(|r| (r.absence_id, r.absence_type, r.absences, r.working_days))
+            .collect();
+        assert_eq!(csv_totals, [("", AbsenceType::Vacation, 2, 3.0), ("", AbsenceType::Sick, 1, 1.0)]);
+    }
+}
@@ -0,0 +1,300 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::config::{Config, PolisherKind};
+use crate::models::{DiffChunk, DiffOp};
+
+#[derive(Debug, Error)]
+pub enum PolishError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("provider returned status {0}")]
+    Status(u16),
+    #[error("unexpected response: {0}")]
+    InvalidResponse(String),
+    #[error("provider echoed the prompt instead of polishing")]
+    EchoedPrompt,
+    #[error("provider returned empty text")]
+    Empty,
+    #[error("timed out")]
+    Timeout,
+}
+
+impl PolishError {
+    /// Connection failures, timeouts, rate limits and server errors may
+    /// succeed on a later attempt; bad requests, undecodable responses and
+    /// bad output will not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            PolishError::Http(err) => {
+                err.is_timeout()
+                    || err.is_connect()
+                    || err.status().is_some_and(|status| is_retryable_status(status.as_u16()))
+            }
+            PolishError::Timeout => true,
+            PolishError::Status(status) => is_retryable_status(*status),
+            PolishError::InvalidResponse(_) | PolishError::EchoedPrompt | PolishError::Empty => false,
+        }
+    }
+}
+
+fn is_retryable_status(status: u16) -> bool {
+    status == 429 || status >= 500
+}
+
+/// Polishes `content` and validates the output, so bad output is reported
+/// as a failure instead of being stored as polished text.
+pub async fn polish_checked(polisher: &dyn FeedbackPolisher, content: &str) -> Result<String, PolishError> {
+    let output = polisher.polish(content).await?;
+    check_output(&output)
+}
+
+/// Word-level diff from the original text to the polished suggestion.
+pub fn word_diff(original: &str, suggested: &str) -> Vec<DiffChunk> {
+    let diff = similar::TextDiff::from_words(original, suggested);
+    let mut chunks: Vec<DiffChunk> = Vec::new();
+
+    for change in diff.iter_all_changes() {
+        let op = match change.tag() {
+            similar::ChangeTag::Equal => DiffOp::Equal,
+            similar::ChangeTag::Insert => DiffOp::Insert,
+            similar::ChangeTag::Delete => DiffOp::Delete,
+        };
+        // Merge consecutive tokens of the same kind into one chunk
+        match chunks.last_mut() {
+            Some(last) if last.op == op => last.text.push_str(change.value()),
+            _ => chunks.push(DiffChunk {
+                op,
+                text: change.value().to_string(),
+            }),
+        }
+    }
+    chunks
+}
+
+/// Completion models such as GPT-2 return the prompt followed by a
+/// continuation rather than a rewrite; anything containing our
+/// instruction text is not a polished version of the feedback.
+fn check_output(output: &str) -> Result<String, PolishError> {
+    let output = output.trim();
+    if output.is_empty() {
+        return Err(PolishError::Empty);
+    }
+    if output.contains(POLISH_INSTRUCTION) {
+        return Err(PolishError::EchoedPrompt);
+    }
+    Ok(output.to_string())
+}
+
+/// Rewrites feedback text into a more polished version with the same meaning.
+#[async_trait]
+pub trait FeedbackPolisher: Send + Sync {
+    /// Short identifier recorded alongside polished text.
+    fn name(&self) -> &'static str;
+
+    async fn polish(&self, content: &str) -> Result<String, PolishError>;
+}
+
+/// Builds the polisher selected by `POLISHER`.
+pub fn from_config(config: &Config) -> Arc<dyn FeedbackPolisher> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.polish_timeout_secs))
+        .build()
+        .expect("failed to build HTTP client");
+
+    match config.polisher {
+        PolisherKind::HuggingFace => Arc::new(HuggingFacePolisher {
+            client,
+            model_url: config.huggingface_model_url.clone(),
+            token: config.huggingface_token.clone(),
+        }),
+        PolisherKind::OpenAi => Arc::new(OpenAiPolisher {
+            client,
+            base_url: config.openai_base_url.trim_end_matches('/').to_string(),
+            api_key: config.openai_api_key.clone(),
+            model: config.openai_model.clone(),
+        }),
+        PolisherKind::RuleBased => Arc::new(RuleBasedPolisher),
+    }
+}
+
+const POLISH_INSTRUCTION: &str =
+    "Please polish and improve the following professional feedback while maintaining its original meaning:";
+
+fn polish_prompt(content: &str) -> String {
+    format!("{} {}", POLISH_INSTRUCTION, content)
+}
+
+/// HuggingFace Inference API text generation model.
+pub struct HuggingFacePolisher {
+    client: reqwest::Client,
+    model_url: String,
+    token: Option<String>,
+}
+
+#[async_trait]
+impl FeedbackPolisher for HuggingFacePolisher {
+    fn name(&self) -> &'static str {
+        "huggingface"
+    }
+
+    async fn polish(&self, content: &str) -> Result<String, PolishError> {
+        let mut request = self.client.post(&self.model_url).json(&json!({
+            "inputs": polish_prompt(content),
+            "parameters": {
+                "max_length": 200,
+                "temperature": 0.7
+            }
+        }));
+
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(PolishError::Status(response.status().as_u16()));
+        }
+
+        let result: Value = response.json().await?;
+        result
+            .pointer("/0/generated_text")
+            .and_then(|text| text.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| PolishError::InvalidResponse("missing [0].generated_text".to_string()))
+    }
+}
+
+/// Any server implementing the OpenAI chat completions API, including
+/// local ones such as llama.cpp, Ollama or vLLM.
+pub struct OpenAiPolisher {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+#[async_trait]
+impl FeedbackPolisher for OpenAiPolisher {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    async fn polish(&self, content: &str) -> Result<String, PolishError> {
+        let mut request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&json!({
+                "model": self.model,
+                "temperature": 0.3,
+                "messages": [
+                    {
+                        "role": "system",
+                        "content": "You edit workplace feedback. Reply with the improved text only."
+                    },
+                    { "role": "user", "content": polish_prompt(content) }
+                ]
+            }));
+
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(PolishError::Status(response.status().as_u16()));
+        }
+
+        let result: Value = response.json().await?;
+        result
+            .pointer("/choices/0/message/content")
+            .and_then(|text| text.as_str())
+            .map(|text| text.trim().to_string())
+            .ok_or_else(|| PolishError::InvalidResponse("missing choices[0].message.content".to_string()))
+    }
+}
+
+/// Deterministic offline polisher: normalizes whitespace, capitalization
+/// and punctuation without changing any words.
+pub struct RuleBasedPolisher;
+
+#[async_trait]
+impl FeedbackPolisher for RuleBasedPolisher {
+    fn name(&self) -> &'static str {
+        "rule-based"
+    }
+
+    async fn polish(&self, content: &str) -> Result<String, PolishError> {
+        Ok(polish_with_rules(content))
+    }
+}
+
+fn polish_with_rules(content: &str) -> String {
+    let words: Vec<&str> = content.split_whitespace().collect();
+    let mut polished = String::with_capacity(content.len());
+    let mut sentence_start = true;
+
+    for word in words {
+        // Drop spaces that ended up before punctuation ("great , thanks")
+        if !polished.is_empty() && !word.starts_with([',', '.', '!', '?', ';', ':']) {
+            polished.push(' ');
+        }
+
+        // Standalone "i" and contractions like "i'm"
+        let word = if word == "i" || word.starts_with("i'") {
+            word.replacen('i', "I", 1)
+        } else {
+            word.to_string()
+        };
+        if sentence_start {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                polished.extend(first.to_uppercase());
+                polished.push_str(chars.as_str());
+            }
+        } else {
+            polished.push_str(&word);
+        }
+
+        sentence_start = word.ends_with(['.', '!', '?']);
+    }
+
+    // Collapse repeated punctuation ("great!!!" -> "great!")
+    let mut collapsed = String::with_capacity(polished.len());
+    for c in polished.chars() {
+        if matches!(c, '!' | '?' | ',') && collapsed.ends_with(c) {
+            continue;
+        }
+        collapsed.push(c);
+    }
+
+    if !collapsed.is_empty() && !collapsed.ends_with(['.', '!', '?']) {
+        collapsed.push('.');
+    }
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_rate_limits_and_server_errors_only() {
+        assert!(PolishError::Status(429).is_retryable());
+        assert!(PolishError::Status(503).is_retryable());
+        assert!(!PolishError::Status(400).is_retryable());
+        assert!(!PolishError::Status(401).is_retryable());
+        assert!(PolishError::Timeout.is_retryable());
+        assert!(!PolishError::EchoedPrompt.is_retryable());
+    }
+
+    #[test]
+    fn does_not_retry_invalid_requests() {
+        let err = reqwest::Client::new().get("not a url").build().unwrap_err();
+        assert!(!PolishError::Http(err).is_retryable());
+    }
+}
@@ -0,0 +1,75 @@
+use crate::models::{ReviewAssignment, ReviewCycle, ReviewQuestionResult, ReviewResults};
+
+/// Aggregates the submitted responses about `employee_id` without
+/// revealing which reviewer gave which answer. With fewer than
+/// `min_responses` responses only the counts are returned, as a small
+/// group's answers could be traced back to the people in it.
+pub fn aggregate_results(cycle: &ReviewCycle, employee_id: &str, min_responses: usize) -> ReviewResults {
+    let assignments: Vec<_> = cycle
+        .assignments
+        .iter()
+        .filter(|a| a.employee_id == employee_id)
+        .collect();
+    let submitted: Vec<&ReviewAssignment> = assignments
+        .iter()
+        .copied()
+        .filter(|a| a.submitted_at.is_some())
+        .collect();
+
+    let withheld = submitted.len() < min_responses;
+    let questions = if withheld { Vec::new() } else { question_results(cycle, &submitted) };
+
+    ReviewResults {
+        cycle_id: cycle.id.clone(),
+        employee_id: employee_id.to_string(),
+        reviewers: assignments.len(),
+        responses: submitted.len(),
+        withheld,
+        questions,
+    }
+}
+
+fn question_results(cycle: &ReviewCycle, submitted: &[&ReviewAssignment]) -> Vec<ReviewQuestionResult> {
+    cycle
+        .questions()
+        .map(|question| {
+            let answers = submitted
+                .iter()
+                .filter_map(|a| a.answers.iter().find(|ans| ans.question_id == question.id));
+
+            match question.kind.scale() {
+                Some((min, max)) => {
+                    let mut counts = vec![0usize; (max - min + 1) as usize];
+                    let mut total = 0i64;
+                    let mut count = 0u32;
+                    for rating in answers.filter_map(|a| a.rating) {
+                        counts[(rating - min) as usize] += 1;
+                        total += i64::from(rating);
+                        count += 1;
+                    }
+                    ReviewQuestionResult {
+                        question_id: question.id.clone(),
+                        text: question.text.clone(),
+                        kind: question.kind.clone(),
+                        average_rating: (count > 0).then(|| total as f64 / f64::from(count)),
+                        rating_counts: Some(counts),
+                        comments: Vec::new(),
+                    }
+                }
+                None => {
+                    let mut comments: Vec<String> = answers.filter_map(|a| a.text.clone()).collect();
+                    // Sorted so the order does not reveal who answered first
+                    comments.sort();
+                    ReviewQuestionResult {
+                        question_id: question.id.clone(),
+                        text: question.text.clone(),
+                        kind: question.kind.clone(),
+                        average_rating: None,
+                        rating_counts: None,
+                        comments,
+                    }
+                }
+            }
+        })
+        .collect()
+}
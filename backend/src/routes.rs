@@ -1,4 +1,5 @@
 use axum::{
+    extract::DefaultBodyLimit,
     middleware::from_fn,
     routing::{get, post, put, delete},
     Router,
@@ -12,24 +13,84 @@ pub fn create_router() -> Router<AppState> {
     let protected_routes = Router::new()
         .route("/users", get(list_users))
         .route("/users", post(create_user))
+        .route("/users/import", post(import_users))
+        .route("/users/export", get(export_users))
         .route("/users/:id", get(get_user))
         .route("/users/:id", put(update_user))
         .route("/users/:id", delete(delete_user))
+        .route("/users/:id/feedback", get(list_user_feedback))
+        .route("/users/:id/reports", get(get_user_reports))
+        .route("/users/:id/chain", get(get_user_chain))
+        .route("/users/:id/working-days", get(get_user_working_days))
+        .route("/users/:id/leave-balances", get(get_leave_balances))
+        .route("/users/:id/leave-ledger", get(get_leave_ledger))
+        .route("/users/:id/leave-adjustments", post(create_leave_adjustment))
+        // Upload size is enforced by the handler against the configured limit
+        .route("/users/:id/avatar", post(upload_avatar).layer(DefaultBodyLimit::disable()))
+        .route("/users/:id/avatar", delete(delete_avatar))
+        .route("/org/tree", get(get_org_tree))
+        .route("/invitations", get(list_invitations))
+        .route("/invitations/:id/resend", post(resend_invitation))
+        .route("/invitations/:id", delete(revoke_invitation))
         .route("/feedback", post(create_feedback))
+        .route("/feedback/authored", get(list_authored_feedback))
+        .route("/feedback/polish-preview", post(polish_preview))
+        .route("/feedback/:id", get(get_feedback))
+        .route("/feedback/:id", put(update_feedback))
+        .route("/feedback/:id", delete(retract_feedback))
+        .route("/feedback/:id/hide", post(hide_feedback))
+        .route("/feedback-templates", get(list_feedback_templates))
+        .route("/feedback-templates", post(create_feedback_template))
+        .route("/feedback-templates/:id", get(get_feedback_template))
+        .route("/feedback-templates/:id", delete(archive_feedback_template))
+        .route("/review-cycles", get(list_review_cycles))
+        .route("/review-cycles", post(create_review_cycle))
+        .route("/review-cycles/:id", get(get_review_cycle))
+        .route("/review-cycles/:id/assignments", post(assign_reviewers))
+        .route("/review-cycles/:id/assignments/:assignment_id", put(submit_review))
+        .route("/review-cycles/:id/close", post(close_review_cycle))
+        .route("/review-cycles/:id/release", post(release_review_cycle))
+        .route("/review-cycles/:id/results/:employee_id", get(get_review_results))
+        .route("/holiday-calendars", get(list_holiday_calendars))
+        .route("/holiday-calendars/:location", get(get_holiday_calendar))
+        .route("/holiday-calendars/:location", put(import_holiday_calendar))
+        .route("/holiday-calendars/:location", delete(delete_holiday_calendar))
         .route("/absences", post(create_absence_request))
         .route("/absences", get(list_all_absences))
         .route("/absences/me", get(get_my_absences))
+        .route("/absences/export", get(export_absences))
+        .route("/absences/approvals", get(list_my_approvals))
+        .route("/absences/calendar", get(get_absence_calendar))
+        .route("/calendar-feed", get(get_calendar_feed))
+        .route("/calendar-feed", put(create_calendar_feed))
+        .route("/calendar-feed", delete(revoke_calendar_feed))
+        .route("/approval-rules", get(get_approval_rules))
+        .route("/approval-rules", put(replace_approval_rules))
+        .route("/delegations", get(list_delegations))
+        .route("/delegations", post(create_delegation))
+        .route("/delegations/:id", delete(delete_delegation))
+        .route("/absences/types", get(list_absence_types))
         .route("/absences/:id/status", put(update_absence_status))
+        .route("/absences/:id/withdraw", post(withdraw_absence_request))
+        .route("/absences/:id/cancel", post(request_absence_cancellation))
         .route("/data-items", get(list_data_items))
         .route("/data-items", post(create_data_item))
         .route("/data-items/:id", get(get_data_item))
         .route("/data-items/:id", put(update_data_item))
         .route("/data-items/:id", delete(delete_data_item))
         .route("/data-items/:id/feedback", post(add_data_item_feedback))
+        .route("/data-items/:id/feedback/:feedback_id", put(update_data_item_feedback))
+        .route("/data-items/:id/feedback/:feedback_id", delete(retract_data_item_feedback))
+        .route("/data-items/:id/feedback/:feedback_id/hide", post(hide_data_item_feedback))
         .layer(from_fn(auth_middleware));
 
     Router::new()
         .route("/auth/login", post(login))
+        .route("/auth/accept-invite", post(accept_invitation))
+        // Calendar clients cannot send a JWT; the secret token authenticates
+        .route("/calendar-feed/:token", get(get_calendar_feed_ics))
+        // Image tags cannot send a JWT; the avatar version in the URL does
+        .route("/users/:id/avatar", get(get_avatar))
         .merge(protected_routes)
 }
 
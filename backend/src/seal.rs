@@ -0,0 +1,151 @@
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+
+use crate::models::{Feedback, Moderated};
+
+const NONCE_LEN: usize = 12;
+
+/// Encrypts the author ID of anonymous feedback so it is never stored in
+/// the clear. Only the server can open a sealed author, and it does so
+/// solely to let authors manage their own entries.
+pub struct AuthorSealer {
+    cipher: ChaCha20Poly1305,
+}
+
+impl AuthorSealer {
+    /// Uses the hex-encoded 32-byte key from `FEEDBACK_SEAL_KEY`, or a random
+    /// per-process key when unset. A key that is set but malformed stops
+    /// startup, as authors sealed with the intended key could not be opened.
+    pub fn from_env() -> Self {
+        let key = match std::env::var("FEEDBACK_SEAL_KEY") {
+            Ok(hex_key) if !hex_key.trim().is_empty() => parse_key(&hex_key)
+                .unwrap_or_else(|err| panic!("invalid value for FEEDBACK_SEAL_KEY: {}", err)),
+            _ => {
+                tracing::warn!("FEEDBACK_SEAL_KEY not set, using a random key for anonymous feedback");
+                ChaCha20Poly1305::generate_key(&mut OsRng)
+            }
+        };
+
+        Self::new(&key)
+    }
+
+    fn new(key: &Key) -> Self {
+        Self {
+            cipher: ChaCha20Poly1305::new(key),
+        }
+    }
+
+    /// Returns hex(nonce || ciphertext).
+    pub fn seal(&self, user_id: &str) -> String {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, user_id.as_bytes())
+            .expect("encrypting a short id cannot fail");
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        hex::encode(sealed)
+    }
+
+    pub fn open(&self, sealed: &str) -> Option<String> {
+        let bytes = hex::decode(sealed).ok()?;
+        if bytes.len() <= NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+}
+
+fn parse_key(hex_key: &str) -> Result<Key, String> {
+    let bytes = hex::decode(hex_key.trim()).map_err(|err| format!("not hex: {}", err))?;
+    if bytes.len() != 32 {
+        return Err(format!("expected 32 bytes, got {}", bytes.len()));
+    }
+    Ok(*Key::from_slice(&bytes))
+}
+
+/// Whether anonymous feedback about `user_id` may be shown: at least
+/// `min_authors` different people left visible anonymous entries for them,
+/// so a single anonymous author cannot be singled out.
+pub fn anonymous_released(feedbacks: &[Feedback], user_id: &str, sealer: &AuthorSealer, min_authors: usize) -> bool {
+    let authors: std::collections::HashSet<String> = feedbacks
+        .iter()
+        .filter(|f| f.user_id == user_id && f.is_visible())
+        .filter_map(|f| f.sealed_author.as_deref().and_then(|sealed| sealer.open(sealed)))
+        .collect();
+    authors.len() >= min_authors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::feedback;
+
+    fn sealer() -> AuthorSealer {
+        AuthorSealer::new(&ChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    /// Anonymous feedback for `user_id` from `author`.
+    fn anonymous(sealer: &AuthorSealer, user_id: &str, author: &str) -> Feedback {
+        Feedback {
+            from_user_id: None,
+            anonymous: true,
+            sealed_author: Some(sealer.seal(author)),
+            ..feedback(&format!("{}-{}", author, user_id), user_id, "Great work")
+        }
+    }
+
+    #[test]
+    fn opens_only_what_it_sealed() {
+        let sealer = sealer();
+        let sealed = sealer.seal("ada");
+        assert_eq!(sealer.open(&sealed).as_deref(), Some("ada"));
+        // A fresh nonce every time, so equal authors do not look alike
+        assert_ne!(sealer.seal("ada"), sealed);
+
+        assert_eq!(self::sealer().open(&sealed), None);
+        let mut tampered = sealed.clone();
+        tampered.replace_range(tampered.len() - 2.., if sealed.ends_with("00") { "01" } else { "00" });
+        assert_eq!(sealer.open(&tampered), None);
+        assert_eq!(sealer.open("not hex"), None);
+        assert_eq!(sealer.open(&sealed[..NONCE_LEN * 2]), None);
+    }
+
+    #[test]
+    fn rejects_malformed_keys() {
+        assert!(parse_key(&"ab".repeat(32)).is_ok());
+        assert!(parse_key(&format!(" {} ", "ab".repeat(32))).is_ok());
+        assert!(parse_key(&"ab".repeat(16)).is_err());
+        assert!(parse_key(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn releases_once_enough_people_wrote() {
+        let sealer = sealer();
+        let mut feedbacks = vec![
+            anonymous(&sealer, "ada", "bob"),
+            anonymous(&sealer, "ada", "bob"),
+            anonymous(&sealer, "ada", "cy"),
+            anonymous(&sealer, "eve", "dan"),
+        ];
+        // Two entries by one person and one for someone else do not count
+        assert!(!anonymous_released(&feedbacks, "ada", &sealer, 3));
+        assert!(anonymous_released(&feedbacks, "ada", &sealer, 2));
+
+        feedbacks.push(anonymous(&sealer, "ada", "dan"));
+        assert!(anonymous_released(&feedbacks, "ada", &sealer, 3));
+
+        // Retracted entries no longer count
+        feedbacks[4].retracted_at = Some(chrono::Utc::now());
+        assert!(!anonymous_released(&feedbacks, "ada", &sealer, 3));
+        assert!(anonymous_released(&feedbacks, "ada", &sealer, 0));
+    }
+}
@@ -2,29 +2,84 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::models::{Feedback, AbsenceRequest, User, DataItem};
+use crate::config::Config;
+use crate::leave::LeaveLedger;
+use crate::jobs::{PolishJobReceiver, PolishQueue};
+use crate::mailer::{FileMailer, Mailer};
+use crate::polish::{self, FeedbackPolisher};
+use crate::seal::AuthorSealer;
+use crate::storage::{LocalDiskStorage, Storage};
+use crate::models::{Feedback, AbsenceRequest, User, DataItem, Invitation, ReviewCycle, FeedbackTemplate, HolidayCalendar, ApprovalRule, Delegation, CalendarFeed, PolishPreview};
 
 pub type FeedbackDb = Arc<RwLock<Vec<Feedback>>>;
 pub type AbsenceDb = Arc<RwLock<Vec<AbsenceRequest>>>;
 pub type UserDb = Arc<RwLock<HashMap<String, User>>>;
 pub type DataItemDb = Arc<RwLock<HashMap<String, DataItem>>>;
+pub type InvitationDb = Arc<RwLock<HashMap<String, Invitation>>>; // Keyed by token hash
+pub type ReviewCycleDb = Arc<RwLock<HashMap<String, ReviewCycle>>>;
+pub type HolidayCalendarDb = Arc<RwLock<HashMap<String, HolidayCalendar>>>;
+pub type ApprovalRuleDb = Arc<RwLock<Vec<ApprovalRule>>>;
+pub type DelegationDb = Arc<RwLock<Vec<Delegation>>>;
+pub type CalendarFeedDb = Arc<RwLock<HashMap<String, CalendarFeed>>>; // Keyed by token hash
+pub type LeaveDb = Arc<RwLock<LeaveLedger>>;
+pub type FeedbackTemplateDb = Arc<RwLock<HashMap<String, FeedbackTemplate>>>;
+pub type PolishPreviewDb = Arc<RwLock<HashMap<String, PolishPreview>>>; // Keyed by preview ID
 
 #[derive(Clone)]
 pub struct AppState {
     pub feedbacks: FeedbackDb,
     pub absences: AbsenceDb,
+    pub leave: LeaveDb,
+    pub approval_rules: ApprovalRuleDb, // Checked in order
+    pub delegations: DelegationDb,
+    pub holiday_calendars: HolidayCalendarDb, // Keyed by location
+    pub calendar_feeds: CalendarFeedDb,
     pub users: UserDb,
     pub data_items: DataItemDb,
+    pub invitations: InvitationDb,
+    pub review_cycles: ReviewCycleDb,
+    pub feedback_templates: FeedbackTemplateDb,
+    pub polish_previews: PolishPreviewDb,
+    pub config: Arc<Config>,
+    pub mailer: Arc<dyn Mailer>,
+    pub storage: Arc<dyn Storage>,
+    pub sealer: Arc<AuthorSealer>,
+    pub polisher: Arc<dyn FeedbackPolisher>,
+    pub polish_queue: PolishQueue,
 }
 
 impl AppState {
-    pub fn new() -> Self {
-        Self {
+    /// Also returns the receiving end of the polish queue, to be handed to
+    /// `jobs::spawn_polish_worker` once the state is set up.
+    pub fn new() -> (Self, PolishJobReceiver) {
+        let config = Config::from_env();
+        let mailer = Arc::new(FileMailer::new(config.mail_outbox_dir.clone()));
+        let storage = Arc::new(LocalDiskStorage::new(config.storage_dir.clone()));
+        let polisher = polish::from_config(&config);
+        let (polish_queue, polish_jobs) = PolishQueue::new();
+
+        let state = Self {
             feedbacks: Arc::new(RwLock::new(Vec::new())),
             absences: Arc::new(RwLock::new(Vec::new())),
+            leave: Arc::new(RwLock::new(LeaveLedger::default())),
+            approval_rules: Arc::new(RwLock::new(Vec::new())),
+            delegations: Arc::new(RwLock::new(Vec::new())),
+            holiday_calendars: Arc::new(RwLock::new(HashMap::new())),
+            calendar_feeds: Arc::new(RwLock::new(HashMap::new())),
             users: Arc::new(RwLock::new(HashMap::new())),
             data_items: Arc::new(RwLock::new(HashMap::new())),
-        }
+            invitations: Arc::new(RwLock::new(HashMap::new())),
+            review_cycles: Arc::new(RwLock::new(HashMap::new())),
+            feedback_templates: Arc::new(RwLock::new(HashMap::new())),
+            polish_previews: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(config),
+            mailer,
+            storage,
+            sealer: Arc::new(AuthorSealer::from_env()),
+            polisher,
+            polish_queue,
+        };
+        (state, polish_jobs)
     }
 }
 
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use crate::models::{CreateFeedbackTemplateRequest, FeedbackAnswer, TemplateQuestion, TemplateQuestionKind};
+
+/// Most points a rating scale may have. Results count every point of the
+/// scale, so this bounds their size.
+pub const MAX_SCALE_POINTS: i64 = 101;
+
+/// Checks that a template has at least one question, that every section
+/// and question has text, and that rating scales make sense.
+pub fn validate_template(req: &CreateFeedbackTemplateRequest) -> Result<(), String> {
+    if req.name.trim().is_empty() {
+        return Err("name is required".to_string());
+    }
+    if req.sections.iter().all(|s| s.questions.is_empty()) {
+        return Err("template has no questions".to_string());
+    }
+
+    for section in &req.sections {
+        if section.title.trim().is_empty() {
+            return Err("section title is required".to_string());
+        }
+        for question in &section.questions {
+            if question.text.trim().is_empty() {
+                return Err(format!("question text is required in section '{}'", section.title));
+            }
+            match &question.kind {
+                TemplateQuestionKind::Numeric { min, max } if min >= max => {
+                    return Err(format!("'{}': min must be below max", question.text));
+                }
+                TemplateQuestionKind::Numeric { min, max } if i64::from(*max) - i64::from(*min) >= MAX_SCALE_POINTS => {
+                    return Err(format!("'{}': a scale has at most {} points", question.text, MAX_SCALE_POINTS));
+                }
+                TemplateQuestionKind::Likert { labels }
+                    if labels.len() < 2 || labels.iter().any(|l| l.trim().is_empty()) =>
+                {
+                    return Err(format!("'{}': needs at least two non-empty labels", question.text));
+                }
+                TemplateQuestionKind::Likert { labels } if labels.len() as i64 > MAX_SCALE_POINTS => {
+                    return Err(format!("'{}': a scale has at most {} points", question.text, MAX_SCALE_POINTS));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks `answers` against a set of template questions, for feedback
+/// and review responses alike: every answer refers to one of `questions`
+/// at most once, required questions are answered, ratings are on the
+/// question's scale and text questions have text.
+pub fn validate_answers<'a>(
+    questions: impl Iterator<Item = &'a TemplateQuestion> + Clone,
+    answers: &[FeedbackAnswer],
+) -> Result<(), String> {
+    let mut by_question: HashMap<&str, &FeedbackAnswer> = HashMap::new();
+    for answer in answers {
+        if !questions.clone().any(|q| q.id == answer.question_id) {
+            return Err(format!("unknown question '{}'", answer.question_id));
+        }
+        if by_question.insert(answer.question_id.as_str(), answer).is_some() {
+            return Err(format!("question '{}' answered twice", answer.question_id));
+        }
+    }
+
+    for question in questions {
+        let Some(answer) = by_question.get(question.id.as_str()) else {
+            if question.required {
+                return Err(format!("question '{}' is required", question.id));
+            }
+            continue;
+        };
+
+        match question.kind.scale() {
+            Some((min, max)) => match answer.rating {
+                Some(rating) if (min..=max).contains(&rating) && answer.text.is_none() => {}
+                _ => {
+                    return Err(format!(
+                        "question '{}' needs a rating from {} to {}",
+                        question.id, min, max
+                    ))
+                }
+            },
+            None => {
+                if answer.rating.is_some() || answer.text.as_deref().is_none_or(|t| t.trim().is_empty()) {
+                    return Err(format!("question '{}' needs a text answer", question.id));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{TemplateQuestionRequest, TemplateSectionRequest};
+
+    fn question(id: &str, required: bool, kind: TemplateQuestionKind) -> TemplateQuestion {
+        TemplateQuestion { id: id.to_string(), text: id.to_string(), required, kind }
+    }
+
+    fn rating(question_id: &str, rating: i32) -> FeedbackAnswer {
+        FeedbackAnswer { question_id: question_id.to_string(), rating: Some(rating), text: None }
+    }
+
+    fn text(question_id: &str, text: &str) -> FeedbackAnswer {
+        FeedbackAnswer { question_id: question_id.to_string(), rating: None, text: Some(text.to_string()) }
+    }
+
+    fn questions() -> Vec<TemplateQuestion> {
+        vec![
+            question("score", true, TemplateQuestionKind::Numeric { min: 0, max: 10 }),
+            question("mood", true, TemplateQuestionKind::Likert { labels: vec!["low".into(), "high".into()] }),
+            question("notes", false, TemplateQuestionKind::Text),
+        ]
+    }
+
+    fn template(kind: TemplateQuestionKind) -> CreateFeedbackTemplateRequest {
+        CreateFeedbackTemplateRequest {
+            name: "Template".to_string(),
+            description: None,
+            sections: vec![TemplateSectionRequest {
+                title: "Section".to_string(),
+                questions: vec![TemplateQuestionRequest { text: "Question".to_string(), required: None, kind }],
+            }],
+        }
+    }
+
+    #[test]
+    fn bounds_rating_scales() {
+        assert!(validate_template(&template(TemplateQuestionKind::Numeric { min: 0, max: 100 })).is_ok());
+        assert!(validate_template(&template(TemplateQuestionKind::Numeric { min: 5, max: 5 })).is_err());
+        assert!(validate_template(&template(TemplateQuestionKind::Numeric { min: 0, max: 101 })).is_err());
+        assert!(validate_template(&template(TemplateQuestionKind::Numeric { min: i32::MIN, max: i32::MAX })).is_err());
+
+        let labels = (0..=MAX_SCALE_POINTS).map(|i| i.to_string()).collect();
+        assert!(validate_template(&template(TemplateQuestionKind::Likert { labels })).is_err());
+    }
+
+    #[test]
+    fn accepts_answers_on_scale_and_skipped_optional_questions() {
+        let answers = [rating("score", 10), rating("mood", 2)];
+        assert_eq!(validate_answers(questions().iter(), &answers), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unknown_duplicate_and_missing_answers() {
+        let unknown = [rating("score", 1), rating("mood", 1), rating("other", 1)];
+        assert_eq!(validate_answers(questions().iter(), &unknown), Err("unknown question 'other'".to_string()));
+
+        let twice = [rating("score", 1), rating("score", 2)];
+        assert_eq!(validate_answers(questions().iter(), &twice), Err("question 'score' answered twice".to_string()));
+
+        let missing = [rating("score", 1)];
+        assert_eq!(validate_answers(questions().iter(), &missing), Err("question 'mood' is required".to_string()));
+    }
+
+    #[test]
+    fn checks_answers_against_question_kind() {
+        let off_scale = [rating("score", 11), rating("mood", 1)];
+        assert_eq!(
+            validate_answers(questions().iter(), &off_scale),
+            Err("question 'score' needs a rating from 0 to 10".to_string())
+        );
+
+        let likert_off_scale = [rating("score", 1), rating("mood", 3)];
+        assert!(validate_answers(questions().iter(), &likert_off_scale).is_err());
+
+        let blank_text = [rating("score", 1), rating("mood", 1), text("notes", " ")];
+        assert_eq!(
+            validate_answers(questions().iter(), &blank_text),
+            Err("question 'notes' needs a text answer".to_string())
+        );
+    }
+}
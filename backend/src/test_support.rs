@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+
+use crate::models::{
+    AbsenceRequest, AbsenceStatus, AbsenceType, Feedback, PolishStatus, PublishedVersion, User, UserStatus,
+    WorkSchedule,
+};
+
+/// A day in January 2024; 2024-01-01 is a Monday.
+pub fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+}
+
+/// An active employee without a manager, on the default schedule. The
+/// ID doubles as name and email prefix.
+pub fn user(id: &str) -> User {
+    User {
+        id: id.to_string(),
+        name: id.to_string(),
+        email: format!("{}@example.com", id),
+        password_hash: String::new(),
+        role: "employee".to_string(),
+        manager_id: None,
+        status: UserStatus::Active,
+        avatar_version: None,
+        location: None,
+        time_zone: None,
+        work_schedule: WorkSchedule::default(),
+    }
+}
+
+/// Users keyed by email, like the user store.
+pub fn user_store(users: impl IntoIterator<Item = User>) -> HashMap<String, User> {
+    users.into_iter().map(|u| (u.email.clone(), u)).collect()
+}
+
+/// A full-day vacation of `user_id` from 09:00 UTC on January `first` to
+/// 17:00 UTC on January `last`, filed on January 1st.
+pub fn absence(user_id: &str, first: u32, last: u32, status: AbsenceStatus) -> AbsenceRequest {
+    AbsenceRequest {
+        id: format!("{}-{}", user_id, first),
+        user_id: user_id.to_string(),
+        absence_type: AbsenceType::Vacation,
+        start_date: Utc.with_ymd_and_hms(2024, 1, first, 9, 0, 0).unwrap(),
+        end_date: Utc.with_ymd_and_hms(2024, 1, last, 17, 0, 0).unwrap(),
+        start_half_day: false,
+        end_half_day: false,
+        working_days: 0.0,
+        reason: String::new(),
+        documentation: None,
+        status,
+        created_at: Utc.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap(),
+        decided_by: None,
+        decided_at: None,
+        decision_comment: None,
+        history: Vec::new(),
+        approvals: Vec::new(),
+    }
+}
+
+/// Feedback for `user_id` from "boss", posted on January 1st.
+pub fn feedback(id: &str, user_id: &str, content: &str) -> Feedback {
+    Feedback {
+        id: id.to_string(),
+        user_id: user_id.to_string(),
+        from_user_id: Some("boss".to_string()),
+        anonymous: false,
+        sealed_author: None,
+        content: content.to_string(),
+        template_id: None,
+        answers: Vec::new(),
+        polished_content: None,
+        polish_status: PolishStatus::Skipped,
+        polish_error: None,
+        published_version: PublishedVersion::Original,
+        created_at: Utc.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap(),
+        updated_at: None,
+        revisions: Vec::new(),
+        retracted_at: None,
+        moderation: None,
+    }
+}
@@ -0,0 +1,28 @@
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// A random secret for one-time links and feed URLs.
+pub fn new_secret_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Secrets are only stored as this hash, so a leaked store does not leak
+/// working links. The tokens are random, so no salt is needed.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_unique_and_hash_deterministically() {
+        let (a, b) = (new_secret_token(), new_secret_token());
+        assert_ne!(a, b);
+        assert_eq!(a.len(), 64);
+        assert_eq!(hash_token(&a), hash_token(&a));
+        assert_ne!(hash_token(&a), hash_token(&b));
+        assert_ne!(hash_token(&a), a);
+    }
+}
@@ -92,8 +92,14 @@ export default function AbsenceRequest() {
   const fetchAbsences = async () => {
     try {
       const endpoint = isManager ? '/absences' : '/absences/me'
-      const response = await api.get(endpoint)
-      setAbsences(response.data)
+      // Lists are paged; fetch every page so sorting and filtering see all requests
+      const items = []
+      for (let page = 1; ; page++) {
+        const response = await api.get(endpoint, { params: { page, per_page: 100 } })
+        items.push(...response.data.items)
+        if (response.data.items.length === 0 || items.length >= response.data.total) break
+      }
+      setAbsences(items)
     } catch (err) {
       setError('Failed to load absence requests')
     } finally {
@@ -143,12 +149,22 @@ export default function AbsenceRequest() {
   }
 
   const handleReject = async (absenceId) => {
+    // Rejections must say why
+    const comment = window.prompt('Why is this request rejected?')
+    if (comment === null) {
+      return
+    }
+    if (!comment.trim()) {
+      setError('A comment is required to reject an absence request')
+      return
+    }
+
     setUpdating(absenceId)
     setError('')
     setSuccess('')
 
     try {
-      await api.put(`/absences/${absenceId}/status`, { status: 'rejected' })
+      await api.put(`/absences/${absenceId}/status`, { status: 'rejected', comment: comment.trim() })
       setSuccess('Absence request rejected!')
       fetchAbsences()
     } catch (err) {
@@ -273,6 +289,9 @@ export default function AbsenceRequest() {
                   <option value="pending">Pending</option>
                   <option value="approved">Approved</option>
                   <option value="rejected">Rejected</option>
+                  <option value="withdrawn">Withdrawn</option>
+                  <option value="cancellation_requested">Cancellation Requested</option>
+                  <option value="cancelled">Cancelled</option>
                 </select>
               </>
             )}
@@ -309,6 +328,9 @@ export default function AbsenceRequest() {
                 pending: 'badge badge-pending',
                 approved: 'badge badge-approved',
                 rejected: 'badge badge-rejected',
+                withdrawn: 'badge badge-withdrawn',
+                cancellation_requested: 'badge badge-pending',
+                cancelled: 'badge badge-withdrawn',
               }[absence.status] || 'badge'
 
               return (
@@ -322,7 +344,7 @@ export default function AbsenceRequest() {
                   <div className="info-row">
                     <div className="info-label">Status:</div>
                     <div className="info-value">
-                      <span className={statusBadge}>{absence.status}</span>
+                      <span className={statusBadge}>{absence.status.replace('_', ' ')}</span>
                     </div>
                   </div>
                   <div className="info-row">
@@ -233,6 +233,46 @@ export default function DataItems() {
     }
   }
 
+  // Feedback arrives as reply threads; removed entries only hold their replies
+  const countFeedbacks = (feedbacks) =>
+    feedbacks.reduce((count, feedback) => count + (feedback.removed ? 0 : 1) + countFeedbacks(feedback.replies || []), 0)
+
+  const renderFeedback = (feedback, depth) => {
+    const feedbackUser = users.find(u => u.id === feedback.from_user_id)
+    return (
+      <div key={feedback.id} style={{ marginLeft: depth > 0 ? '1rem' : 0 }}>
+        <div style={{ padding: '0.75rem', background: '#fafafa', border: '1px solid #e0e0e0', borderRadius: '2px', marginBottom: '0.5rem' }}>
+          {feedback.removed ? (
+            <div style={{ fontSize: '0.875rem', color: '#999', fontStyle: 'italic' }}>
+              Feedback removed
+            </div>
+          ) : (
+            <>
+              <div className="text-truncate" style={{ fontSize: '0.75rem', color: '#666', marginBottom: '0.5rem', fontWeight: '500' }}>
+                {feedbackUser ? feedbackUser.name : 'Unknown'} - {format(new Date(feedback.created_at), 'MMM d, yyyy')}
+              </div>
+              {feedback.polished_content ? (
+                <div>
+                  <div className="text-truncate-2" style={{ fontSize: '0.875rem', color: '#999', marginBottom: '0.5rem' }}>
+                    Original: {feedback.content}
+                  </div>
+                  <div className="text-truncate-3" style={{ fontSize: '0.875rem', color: '#2196F3', fontWeight: '500' }}>
+                    ✨ AI-Enhanced: {feedback.polished_content}
+                  </div>
+                </div>
+              ) : (
+                <div className="text-truncate-3" style={{ fontSize: '0.875rem', wordBreak: 'break-word' }}>
+                  {feedback.content}
+                </div>
+              )}
+            </>
+          )}
+        </div>
+        {(feedback.replies || []).map((reply) => renderFeedback(reply, depth + 1))}
+      </div>
+    )
+  }
+
   if (loading) {
     return <div className="loading">Loading...</div>
   }
@@ -496,32 +536,9 @@ export default function DataItems() {
                   {/* Display Feedbacks */}
                   {item.feedbacks && item.feedbacks.length > 0 && (
                     <div style={{ marginTop: '1rem', paddingTop: '1rem', borderTop: '1px solid #e0e0e0' }}>
-                      <h4 style={{ fontSize: '0.875rem', fontWeight: '600', marginBottom: '0.5rem' }}>Feedbacks ({item.feedbacks.length})</h4>
+                      <h4 style={{ fontSize: '0.875rem', fontWeight: '600', marginBottom: '0.5rem' }}>Feedbacks ({countFeedbacks(item.feedbacks)})</h4>
                       <div className="scrollable" style={{ maxHeight: '200px' }}>
-                        {item.feedbacks.map((feedback) => {
-                        const feedbackUser = users.find(u => u.id === feedback.from_user_id)
-                        return (
-                          <div key={feedback.id} style={{ padding: '0.75rem', background: '#fafafa', border: '1px solid #e0e0e0', borderRadius: '2px', marginBottom: '0.5rem' }}>
-                            <div className="text-truncate" style={{ fontSize: '0.75rem', color: '#666', marginBottom: '0.5rem', fontWeight: '500' }}>
-                              {feedbackUser ? feedbackUser.name : 'Unknown'} - {format(new Date(feedback.created_at), 'MMM d, yyyy')}
-                            </div>
-                            {feedback.polished_content ? (
-                              <div>
-                                <div className="text-truncate-2" style={{ fontSize: '0.875rem', color: '#999', marginBottom: '0.5rem' }}>
-                                  Original: {feedback.content}
-                                </div>
-                                <div className="text-truncate-3" style={{ fontSize: '0.875rem', color: '#2196F3', fontWeight: '500' }}>
-                                  ✨ AI-Enhanced: {feedback.polished_content}
-                                </div>
-                              </div>
-                            ) : (
-                              <div className="text-truncate-3" style={{ fontSize: '0.875rem', wordBreak: 'break-word' }}>
-                                {feedback.content}
-                              </div>
-                            )}
-                          </div>
-                        )
-                      })}
+                        {item.feedbacks.map((feedback) => renderFeedback(feedback, 0))}
                       </div>
                     </div>
                   )}
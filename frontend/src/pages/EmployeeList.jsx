@@ -13,7 +13,6 @@ export default function UserManagement() {
   const [formData, setFormData] = useState({
     name: '',
     email: '',
-    password: '',
     role: 'employee',
   })
   const [submitting, setSubmitting] = useState(false)
@@ -89,13 +88,12 @@ export default function UserManagement() {
       const payload = {
         name: formData.name,
         email: formData.email,
-        password: formData.password,
         role: formData.role,
       }
 
       await api.post('/users', payload)
-      setSuccess('User created successfully!')
-      setFormData({ name: '', email: '', password: '', role: 'employee' })
+      setSuccess('Invitation sent!')
+      setFormData({ name: '', email: '', role: 'employee' })
       setShowUserForm(false)
       fetchUsers()
     } catch (err) {
@@ -215,19 +213,6 @@ export default function UserManagement() {
               />
             </div>
 
-            <div className="form-group">
-              <label htmlFor="user-password">Password</label>
-              <input
-                id="user-password"
-                type="password"
-                value={formData.password}
-                onChange={(e) => setFormData({ ...formData, password: e.target.value })}
-                required
-                minLength={6}
-                placeholder="Minimum 6 characters"
-              />
-            </div>
-
             <div className="form-group">
               <label htmlFor="user-role">Role</label>
               <select
@@ -244,14 +229,14 @@ export default function UserManagement() {
 
             <div style={{ display: 'flex', gap: '1rem' }}>
               <button type="submit" className="btn btn-primary" disabled={submitting}>
-                {submitting ? 'Creating...' : 'Create User'}
+                {submitting ? 'Inviting...' : 'Invite User'}
               </button>
               <button 
                 type="button" 
                 className="btn btn-secondary" 
                 onClick={() => {
                   setShowUserForm(false)
-                  setFormData({ name: '', email: '', password: '', role: 'employee' })
+                  setFormData({ name: '', email: '', role: 'employee' })
                   setError('')
                 }}
               >
@@ -301,6 +286,10 @@ export default function UserManagement() {
                   className="employee-card"
                 >
                   <div style={{ display: 'flex', justifyContent: 'space-between', alignItems: 'flex-start', marginBottom: '1rem', gap: '1rem' }}>
+                    {/* avatar_url needs no token, so a plain img tag can load it */}
+                    {u.avatar_url && (
+                      <img className="avatar" src={`${u.avatar_url}&size=64`} alt="" />
+                    )}
                     <div style={{ flex: 1, minWidth: 0 }}>
                       <h3 className="text-truncate" style={{ margin: '0 0 0.5rem 0' }}>
                         {u.name}